@@ -0,0 +1,12 @@
+-- related tags per co-occurrence on a same rut or item, refreshed by task
+
+CREATE TABLE relatedtags (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  tname VARCHAR NOT NULL,
+  related VARCHAR NOT NULL,
+  co_count INTEGER NOT NULL DEFAULT '0',
+  score FLOAT8 NOT NULL DEFAULT '0',
+  cal_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (tname, related),
+  CHECK (tname != related)
+);
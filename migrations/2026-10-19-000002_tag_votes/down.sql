@@ -0,0 +1,2 @@
+ALTER TABLE tagruts DROP COLUMN locked;
+DROP TABLE tagvotes;
@@ -0,0 +1,14 @@
+-- record who tag a rut|item, one vote per user per tag per target
+
+CREATE TABLE tagvotes (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  uname VARCHAR NOT NULL,
+  tname VARCHAR NOT NULL,
+  vote_to VARCHAR NOT NULL,
+  to_id VARCHAR NOT NULL,
+  vote_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (uname, tname, vote_to, to_id)
+);
+
+-- rut owner can lock a tag on rut
+ALTER TABLE tagruts ADD COLUMN locked BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+DROP INDEX etcs_petc_id_idx;
+ALTER TABLE etcs DROP COLUMN reply_count;
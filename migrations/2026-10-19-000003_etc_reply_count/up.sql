@@ -0,0 +1,23 @@
+-- reply count of an etc, to build comment tree
+
+ALTER TABLE etcs ADD COLUMN reply_count INTEGER NOT NULL DEFAULT '0';
+
+-- replies inherit rut|item|tag of the thread root
+WITH RECURSIVE thread AS (
+  SELECT id, rut_id, item_id, tname FROM etcs WHERE petc_id = ''
+  UNION ALL
+  SELECT e.id, t.rut_id, t.item_id, t.tname FROM etcs e JOIN thread t ON e.petc_id = t.id
+)
+UPDATE etcs SET rut_id = thread.rut_id, item_id = thread.item_id, tname = thread.tname
+FROM thread WHERE etcs.id = thread.id AND etcs.petc_id != '';
+
+UPDATE etcs SET reply_count = r.n FROM (
+  SELECT petc_id, COUNT(*) AS n FROM etcs WHERE petc_id != '' GROUP BY petc_id
+) r WHERE etcs.id = r.petc_id;
+
+-- replies counted in rut since, recount the existing
+UPDATE ruts SET comment_count = (
+  SELECT COUNT(*) FROM etcs WHERE etcs.rut_id = ruts.id
+);
+
+CREATE INDEX etcs_petc_id_idx ON etcs (petc_id);
@@ -0,0 +1,5 @@
+DELETE FROM etcs WHERE deleted;
+DROP INDEX etcs_content_uname_key;
+ALTER TABLE etcs ADD CONSTRAINT etcs_content_uname_key UNIQUE (content, uname);
+ALTER TABLE etcs DROP COLUMN deleted;
+ALTER TABLE etcs DROP COLUMN edited_at;
@@ -0,0 +1,13 @@
+-- edit marker, and tombstone for deleted etc with replies
+
+ALTER TABLE etcs ADD COLUMN edited_at TIMESTAMP;
+ALTER TABLE etcs ADD COLUMN deleted BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- tombstones share empty content, keep unique only on the live
+ALTER TABLE etcs DROP CONSTRAINT etcs_content_uname_key;
+CREATE UNIQUE INDEX etcs_content_uname_key ON etcs (content, uname) WHERE NOT deleted;
+
+-- etc_count of item maintained since, recount the existing
+UPDATE items SET etc_count = (
+  SELECT COUNT(*) FROM etcs WHERE etcs.item_id = items.id
+);
@@ -0,0 +1,5 @@
+UPDATE etcs SET vote = 1;
+ALTER TABLE etcs ALTER COLUMN vote SET DEFAULT '1';
+ALTER TABLE items DROP COLUMN score;
+ALTER TABLE ruts DROP COLUMN score;
+DROP TABLE votes;
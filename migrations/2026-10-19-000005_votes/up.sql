@@ -0,0 +1,20 @@
+-- up/down vote on etc|rut|item, one per user per target
+
+CREATE TABLE votes (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  uname VARCHAR NOT NULL,
+  vote_to VARCHAR NOT NULL,
+  to_id VARCHAR NOT NULL,
+  updown SMALLINT NOT NULL,
+  vote_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (uname, vote_to, to_id),
+  CHECK (updown = 1 OR updown = -1)
+);
+
+-- net score of votes
+ALTER TABLE ruts ADD COLUMN score INTEGER NOT NULL DEFAULT '0';
+ALTER TABLE items ADD COLUMN score INTEGER NOT NULL DEFAULT '0';
+
+-- etcs.vote as net score of votes
+ALTER TABLE etcs ALTER COLUMN vote SET DEFAULT '0';
+UPDATE etcs SET vote = 0;
@@ -0,0 +1,6 @@
+DROP INDEX etcs_item_id_kind_idx;
+ALTER TABLE etcs DROP COLUMN percent;
+ALTER TABLE etcs DROP COLUMN location;
+ALTER TABLE etcs DROP COLUMN chapter;
+ALTER TABLE etcs DROP COLUMN page;
+ALTER TABLE etcs DROP COLUMN kind;
@@ -0,0 +1,9 @@
+-- kind of etc: comment|excerpt|note|review, and location of excerpt in item
+
+ALTER TABLE etcs ADD COLUMN kind VARCHAR NOT NULL DEFAULT 'comment';
+ALTER TABLE etcs ADD COLUMN page INTEGER;
+ALTER TABLE etcs ADD COLUMN chapter VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE etcs ADD COLUMN location VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE etcs ADD COLUMN percent REAL;
+
+CREATE INDEX etcs_item_id_kind_idx ON etcs (item_id, kind);
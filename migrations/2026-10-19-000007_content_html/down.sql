@@ -0,0 +1,5 @@
+ALTER TABLE users DROP COLUMN intro_html;
+ALTER TABLE tags DROP COLUMN intro_html;
+ALTER TABLE etcs DROP COLUMN content_html;
+ALTER TABLE collects DROP COLUMN content_html;
+ALTER TABLE ruts DROP COLUMN content_html;
@@ -0,0 +1,7 @@
+-- rendered and sanitized html of user content, backfilled by task on start
+
+ALTER TABLE ruts ADD COLUMN content_html TEXT NOT NULL DEFAULT '';
+ALTER TABLE collects ADD COLUMN content_html TEXT NOT NULL DEFAULT '';
+ALTER TABLE etcs ADD COLUMN content_html TEXT NOT NULL DEFAULT '';
+ALTER TABLE tags ADD COLUMN intro_html TEXT NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN intro_html TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,15 @@
+-- @uname mentions in etc and collect content
+
+CREATE TABLE mentions (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  uname VARCHAR NOT NULL,      -- who is mentioned
+  by_uname VARCHAR NOT NULL,   -- who mention
+  mention_to VARCHAR NOT NULL, -- etc|collect
+  to_id VARCHAR NOT NULL,
+  rut_id VARCHAR NOT NULL DEFAULT '',
+  item_id VARCHAR NOT NULL DEFAULT '',
+  mention_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (uname, mention_to, to_id)
+);
+
+CREATE INDEX mentions_to_idx ON mentions (mention_to, to_id);
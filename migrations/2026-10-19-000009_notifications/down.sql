@@ -0,0 +1,2 @@
+DROP TABLE notifysettings;
+DROP TABLE notifications;
@@ -0,0 +1,24 @@
+-- in-app notification, and per-action preference of user
+
+CREATE TABLE notifications (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  uname VARCHAR NOT NULL,  -- who to notify
+  actor VARCHAR NOT NULL,  -- who act
+  action VARCHAR NOT NULL, -- star_rut|comment_rut|collect_rut|reply_etc|mention|follow
+  obj VARCHAR NOT NULL,    -- rut|etc|collect|user
+  objid VARCHAR NOT NULL,
+  content VARCHAR NOT NULL DEFAULT '', -- excerpt of content, title
+  is_read BOOLEAN NOT NULL DEFAULT FALSE,
+  notify_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX notifications_uname_idx ON notifications (uname, is_read, notify_at);
+
+-- w/o row as enabled
+CREATE TABLE notifysettings (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  uname VARCHAR NOT NULL,
+  action VARCHAR NOT NULL,
+  enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  UNIQUE (uname, action)
+);
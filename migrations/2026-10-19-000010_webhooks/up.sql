@@ -0,0 +1,30 @@
+-- outgoing webhook of user, and delivery log as outbox
+
+CREATE TABLE webhooks (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  uname VARCHAR NOT NULL,
+  url VARCHAR NOT NULL,
+  secret VARCHAR NOT NULL, -- to sign payload
+  events TEXT[] NOT NULL DEFAULT '{}', -- rut_created|rut_updated|item_collected|etc_posted|item_done
+  active BOOLEAN NOT NULL DEFAULT TRUE,
+  create_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX webhooks_uname_idx ON webhooks (uname);
+
+CREATE TABLE deliveries (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  webhook_id VARCHAR NOT NULL,
+  event VARCHAR NOT NULL,
+  payload TEXT NOT NULL,
+  status VARCHAR NOT NULL DEFAULT 'pending', -- pending|success|failed
+  attempts INTEGER NOT NULL DEFAULT 0,
+  response_code INTEGER,
+  response_body TEXT NOT NULL DEFAULT '',
+  create_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  next_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP, -- next attempt
+  deliver_at TIMESTAMP -- last attempt
+);
+
+CREATE INDEX deliveries_webhook_id_idx ON deliveries (webhook_id, create_at);
+CREATE INDEX deliveries_pending_idx ON deliveries (next_at) WHERE status = 'pending';
@@ -0,0 +1,9 @@
+-- email digest preference of user, w/o row as off, opt in
+
+CREATE TABLE digestsettings (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  uname VARCHAR NOT NULL,
+  frequency VARCHAR NOT NULL DEFAULT 'off', -- weekly|daily|off
+  last_sent TIMESTAMP,
+  UNIQUE (uname)
+);
@@ -0,0 +1,7 @@
+DROP INDEX etcs_tsv_idx;
+DROP INDEX items_tsv_idx;
+DROP INDEX ruts_tsv_idx;
+
+ALTER TABLE etcs DROP COLUMN tsv;
+ALTER TABLE items DROP COLUMN tsv;
+ALTER TABLE ruts DROP COLUMN tsv;
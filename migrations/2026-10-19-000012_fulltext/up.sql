@@ -0,0 +1,24 @@
+-- full-text search: generated tsvector per searchable table, weighted, w/ gin index
+-- kept out of schema.rs, only used in raw sql, see db::search
+
+ALTER TABLE ruts ADD COLUMN tsv tsvector GENERATED ALWAYS AS (
+  setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+  setweight(to_tsvector('english', coalesce(author, '')), 'B') ||
+  setweight(to_tsvector('english', coalesce(content, '')), 'C')
+) STORED;
+
+ALTER TABLE items ADD COLUMN tsv tsvector GENERATED ALWAYS AS (
+  setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+  setweight(to_tsvector('english', coalesce(uiid, '')), 'A') ||
+  setweight(to_tsvector('english', coalesce(authors, '')), 'B') ||
+  setweight(to_tsvector('english', coalesce(publisher, '')), 'C') ||
+  setweight(to_tsvector('english', coalesce(detail, '')), 'D')
+) STORED;
+
+ALTER TABLE etcs ADD COLUMN tsv tsvector GENERATED ALWAYS AS (
+  to_tsvector('english', coalesce(content, ''))
+) STORED;
+
+CREATE INDEX ruts_tsv_idx ON ruts USING GIN (tsv);
+CREATE INDEX items_tsv_idx ON items USING GIN (tsv);
+CREATE INDEX etcs_tsv_idx ON etcs USING GIN (tsv);
@@ -0,0 +1,3 @@
+DROP INDEX items_uiid_trgm_idx;
+DROP INDEX items_authors_trgm_idx;
+DROP INDEX items_title_trgm_idx;
@@ -0,0 +1,7 @@
+-- fuzzy lookup of item per trigram similarity, typo-tolerant
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX items_title_trgm_idx ON items USING GIN (title gin_trgm_ops);
+CREATE INDEX items_authors_trgm_idx ON items USING GIN (authors gin_trgm_ops);
+CREATE INDEX items_uiid_trgm_idx ON items USING GIN (uiid gin_trgm_ops);
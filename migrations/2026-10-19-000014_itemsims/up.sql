@@ -0,0 +1,14 @@
+-- item-item similarity per co-occurrence in staritems and collects, refreshed by task
+
+CREATE TABLE itemsims (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  item_id VARCHAR NOT NULL,
+  similar_id VARCHAR NOT NULL,
+  co_count INTEGER NOT NULL DEFAULT '0',
+  score FLOAT8 NOT NULL DEFAULT '0',
+  cal_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (item_id, similar_id),
+  CHECK (item_id != similar_id)
+);
+
+CREATE INDEX itemsims_item_id_score_idx ON itemsims (item_id, score DESC);
@@ -0,0 +1,3 @@
+DROP INDEX ruts_visibility_idx;
+
+ALTER TABLE ruts DROP COLUMN visibility;
@@ -0,0 +1,5 @@
+-- visibility of rut: public, unlisted (only via slug), private (only owner)
+
+ALTER TABLE ruts ADD COLUMN visibility VARCHAR NOT NULL DEFAULT 'public';
+
+CREATE INDEX ruts_visibility_idx ON ruts (visibility);
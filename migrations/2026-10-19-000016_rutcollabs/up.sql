@@ -0,0 +1,15 @@
+-- collaborators of rut, invited by owner, role: editor|viewer
+
+CREATE TABLE rutcollabs (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  rut_id VARCHAR NOT NULL,
+  uname VARCHAR NOT NULL,
+  role VARCHAR NOT NULL DEFAULT 'viewer',
+  accepted BOOLEAN NOT NULL DEFAULT FALSE,
+  invite_by VARCHAR NOT NULL,
+  invite_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  accept_at TIMESTAMP,
+  UNIQUE (rut_id, uname)
+);
+
+CREATE INDEX rutcollabs_uname_idx ON rutcollabs (uname);
@@ -0,0 +1,18 @@
+-- item suggested to others' rut, accepted as collect or declined by owner|editor
+
+CREATE TABLE rutsuggests (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  rut_id VARCHAR NOT NULL,
+  item_id VARCHAR NOT NULL,
+  uname VARCHAR NOT NULL,
+  note TEXT NOT NULL DEFAULT '',
+  status VARCHAR NOT NULL DEFAULT 'pending',
+  reason TEXT NOT NULL DEFAULT '',
+  decide_by VARCHAR NOT NULL DEFAULT '',
+  collect_id VARCHAR NOT NULL DEFAULT '',
+  suggest_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  decide_at TIMESTAMP
+);
+
+CREATE INDEX rutsuggests_rut_id_status_idx ON rutsuggests (rut_id, status);
+CREATE INDEX rutsuggests_uname_idx ON rutsuggests (uname);
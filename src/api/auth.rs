@@ -9,7 +9,7 @@ use futures::{future::result, Future};
 
 use crate::model::msg::{AuthMsg, UserMsg};
 use crate::model::user::{
-    encode_token, AuthUser, ChangePsw, CheckUser, QueryUser, RegUser, UpdateUser,
+    encode_token, AuthUser, ChangePsw, CheckUser, FollowUser, QueryUser, RegUser, UpdateUser,
 };
 use crate::model::Validate;
 use crate::DbAddr;
@@ -126,6 +126,28 @@ pub fn change_psw(
         })
 }
 
+// "/followuser/{fname}/{action:[0|1]}" POST
+pub fn follow_user(
+    db: Data<DbAddr>,
+    fo_info: Path<(String, u8)>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let fname = fo_info.clone().0;
+    let action: u8 = fo_info.1;
+    let uname = auth.uname;
+
+    db.send(FollowUser {
+        uname,
+        fname,
+        action,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
+
 pub fn auth_token(user: CheckUser) -> HttpResponse {
     HttpResponse::Ok().json(user)
 }
@@ -0,0 +1,92 @@
+// api.collab, view handler: collaborators of rut
+
+use actix_web::{
+    web::{Data, Json, Path},
+    Error, HttpResponse, ResponseError,
+};
+use futures::{future::result, Future};
+
+use crate::model::collab::{AcceptCollab, InviteCollab, QueryCollabs, RemoveCollab};
+use crate::model::user::CheckUser;
+use crate::model::Validate;
+use crate::DbAddr;
+
+// "/rutcollabs/{rutid}" POST, {uname, role: editor|viewer}
+pub fn invite(
+    db: Data<DbAddr>,
+    rid: Path<String>,
+    inv: Json<InviteCollab>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = rid.into_inner();
+    let invite_by = auth.uname; // pass to handler to check permission
+    let invite = InviteCollab {
+        rut_id,
+        invite_by,
+        ..inv.into_inner()
+    };
+
+    result(invite.validate())
+        .from_err()
+        .and_then(move |_| db.send(invite).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
+
+// "/rutcollabs/{rutid}" GET
+pub fn get_list(
+    db: Data<DbAddr>,
+    rid: Path<String>,
+    auth: Option<CheckUser>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = rid.into_inner();
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
+
+    db.send(QueryCollabs { rut_id, viewer })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/acceptcollab/{rutid}" POST
+pub fn accept(
+    db: Data<DbAddr>,
+    rid: Path<String>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = rid.into_inner();
+    let uname = auth.uname;
+
+    db.send(AcceptCollab { rut_id, uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/rutcollabs/{rutid}/{uname}" DELETE, by owner, or leave by self
+pub fn remove(
+    db: Data<DbAddr>,
+    info: Path<(String, String)>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = info.clone().0;
+    let uname = info.clone().1;
+    let action_by = auth.uname;
+
+    db.send(RemoveCollab {
+        rut_id,
+        uname,
+        action_by,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
@@ -0,0 +1,46 @@
+// api.digest, view handler: email digest preference
+
+use actix_web::{
+    web::{self, Data, Json},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::{future::result, Future};
+
+use crate::model::digest::{QueryDigestSetting, UpdateDigestSetting};
+use crate::model::user::CheckUser;
+use crate::model::Validate;
+use crate::DbAddr;
+
+// "/digestsettings" GET
+pub fn get_setting(
+    db: Data<DbAddr>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let uname = auth.uname;
+
+    db.send(QueryDigestSetting { uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/digestsettings" PUT
+pub fn update_setting(
+    db: Data<DbAddr>,
+    setting: Json<UpdateDigestSetting>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let up_setting = setting.into_inner();
+    let uname = auth.uname;
+    let new_setting = UpdateDigestSetting { uname, ..up_setting };
+
+    result(new_setting.validate())
+        .from_err()
+        .and_then(move |_| db.send(new_setting).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
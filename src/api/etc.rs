@@ -6,8 +6,8 @@ use actix_web::{
 };
 use futures::{future::result, Future};
 
-use crate::api::ReqQuery;
-use crate::model::etc::{Etc, PostEtc, QueryEtcs};
+use crate::api::{EtcQuery, TreeQuery};
+use crate::model::etc::{DelEtc, Etc, PostEtc, QueryEtcTree, QueryEtcs, UpdateEtc};
 use crate::model::user::CheckUser;
 use crate::model::Validate;
 use crate::DbAddr;
@@ -30,21 +30,102 @@ pub fn new(
         })
 }
 
+// "/etcs" PUT, edit by author only
+pub fn update(
+    db: Data<DbAddr>,
+    uetc: Json<UpdateEtc>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let up_etc = uetc.into_inner();
+    let uname = auth.uname; // pass to handler to check permission
+    let etc_up = UpdateEtc { uname, ..up_etc };
+
+    result(etc_up.validate())
+        .from_err()
+        .and_then(move |_| db.send(etc_up).from_err())
+        .and_then(|res| match res {
+            Ok(et) => Ok(HttpResponse::Ok().json(et)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
+
+// "/etcs/{etcid}" DELETE, by author only
+pub fn delete(
+    db: Data<DbAddr>,
+    eid: Path<String>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let etc_id = eid.into_inner();
+    let uname = auth.uname; // pass to handler to check permission
+
+    db.send(DelEtc { etc_id, uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/etcs/{per}/{perid}?page=&flag=&kind=" GET
 pub fn get_list(
     db: Data<DbAddr>,
-    pq: Query<ReqQuery>,
+    pq: Query<EtcQuery>,
     per_info: Path<(String, String)>,
+    auth: Option<CheckUser>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     // extract Path
     let per = per_info.clone().0;
     let perid = per_info.clone().1;
     // extract Query
     let page = std::cmp::max(pq.page, 1);
+    let sort = pq.clone().flag; // time|score|location
+    let kind = pq.clone().kind; // e.g. excerpt as highlights
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
 
-    db.send(QueryEtcs { per, perid, page })
-        .from_err()
-        .and_then(|res| match res {
-            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
-            Err(err) => Ok(err.error_response()),
-        })
+    db.send(QueryEtcs {
+        per,
+        perid,
+        page,
+        sort,
+        kind,
+        viewer,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
+
+// "/etcs/{per}/{perid}/tree?page=&depth=&sort=" GET
+pub fn get_tree(
+    db: Data<DbAddr>,
+    tq: Query<TreeQuery>,
+    per_info: Path<(String, String)>,
+    auth: Option<CheckUser>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    // extract Path
+    let per = per_info.clone().0;
+    let perid = per_info.clone().1;
+    // extract Query
+    let page = std::cmp::max(tq.page, 1);
+    let depth = tq.depth;
+    let sort = tq.clone().sort;
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
+
+    db.send(QueryEtcTree {
+        per,
+        perid,
+        depth,
+        sort,
+        page,
+        viewer,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
 }
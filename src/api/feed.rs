@@ -0,0 +1,91 @@
+// api.feed, view handler: atom|rss feed of rut, tag, user, index
+
+use actix_web::{
+    http::header,
+    web::{self, Data, Path, Query},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::Future;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::api::FeedQuery;
+use crate::model::feed::{Feed, QueryFeed};
+use crate::util::feed::{http_date, parse_http_date, to_atom, to_rss};
+use crate::DbAddr;
+
+// "/feeds/{per}/{perid}?format=atom|rss" GET, conditional get per etag, last-modified
+pub fn get_feed(
+    req: HttpRequest,
+    db: Data<DbAddr>,
+    fq: Query<FeedQuery>,
+    per_info: Path<(String, String)>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let per = per_info.clone().0;
+    let perid = per_info.clone().1;
+    let is_rss = fq.format.trim() == "rss";
+
+    db.send(QueryFeed { per, perid })
+        .from_err()
+        .and_then(move |res| match res {
+            Ok(feed) => {
+                let etag = feed_etag(&feed, is_rss);
+                let last_modified = http_date(&feed.updated);
+                if not_modified(&req, &feed, &etag) {
+                    return Ok(HttpResponse::NotModified()
+                        .header(header::ETAG, etag)
+                        .header(header::LAST_MODIFIED, last_modified)
+                        .finish());
+                }
+
+                let conn_info = req.connection_info();
+                let self_link = format!(
+                    "{}://{}{}",
+                    conn_info.scheme(),
+                    conn_info.host(),
+                    req.uri()
+                );
+                let (body, content_type) = if is_rss {
+                    (to_rss(&feed, &self_link), "application/rss+xml; charset=utf-8")
+                } else {
+                    (to_atom(&feed, &self_link), "application/atom+xml; charset=utf-8")
+                };
+
+                Ok(HttpResponse::Ok()
+                    .content_type(content_type)
+                    .header(header::ETAG, etag)
+                    .header(header::LAST_MODIFIED, last_modified)
+                    .body(body))
+            }
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// weak etag per format, updated and entries
+fn feed_etag(feed: &Feed, is_rss: bool) -> String {
+    let mut hasher = DefaultHasher::new();
+    is_rss.hash(&mut hasher);
+    feed.updated.hash(&mut hasher);
+    for e in feed.entries.iter() {
+        e.id.hash(&mut hasher);
+        e.updated.hash(&mut hasher);
+    }
+    format!("W/\"{:x}\"", hasher.finish())
+}
+
+fn not_modified(req: &HttpRequest, feed: &Feed, etag: &str) -> bool {
+    let headers = req.headers();
+    // if-none-match takes precedence
+    if let Some(inm) = headers.get(header::IF_NONE_MATCH).and_then(|h| h.to_str().ok()) {
+        return inm.split(',').any(|t| t.trim() == etag || t.trim() == "*");
+    }
+    if let Some(ims) = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(parse_http_date)
+    {
+        // http date in secs
+        return feed.updated.timestamp() <= ims.timestamp();
+    }
+    false
+}
@@ -74,8 +74,8 @@ pub fn get_list(
     let itemsPerID = match per {
         // hope can fuzzy query per uiid..url, contains
         // here are some issue, 400 or no result, % trimed
-        "uiid" => QueryItems::Uiid(perid),
-        "title" => QueryItems::Title(perid),
+        "uiid" => QueryItems::Uiid(perid), // fuzzy per trigram, as item picker in collect
+        "title" => QueryItems::Title(perid), // w/ did-you-mean suggestion
         "url" => QueryItems::ItemUrl(
             String::from_utf8(
                 decode(&kw).unwrap_or(Vec::new()), //url as kw, avoid route error
@@ -84,7 +84,7 @@ pub fn get_list(
         ),
         // query per relations with  rut, tag, user
         "rut" => QueryItems::RutID(perid),
-        "tag" => QueryItems::TagID(perid),
+        "tag" => QueryItems::TagID(perid, flag), // flag=count|score
         "user" => QueryItems::UserID(perid, flag.parse::<i16>().unwrap_or(3), page),
         "key" => QueryItems::KeyID(kw, fr, perid, page),
         _ => QueryItems::ItemID(perid),
@@ -132,8 +132,13 @@ pub fn collect_item(
     auth: CheckUser,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     // todo some check of input
+    let uname = auth.uname; // pass to handler to check permission
+    let collect = CollectItem {
+        uname,
+        ..c_item.into_inner()
+    };
 
-    db.send(c_item.into_inner())
+    db.send(collect)
         .from_err()
         .and_then(|res| match res {
             Ok(item) => Ok(HttpResponse::Ok().json(item)),
@@ -145,18 +150,21 @@ pub fn get_collect_list(
     db: Data<DbAddr>,
     pq: Query<ReqQuery>,
     per_info: Path<(String, String)>,
+    auth: Option<CheckUser>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     // extract Path
     let per = per_info.0.trim();
     let perid = per_info.clone().1;
     // extract Query
     let page = std::cmp::max(pq.page, 1);
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
 
     let collectIDs = match per {
         "item" => QueryCollects::ItemID(perid, page),
-        "rut" => QueryCollects::RutID(perid),
-        "user" => QueryCollects::UserID(perid, page),
-        _ => QueryCollects::RutID(perid),
+        "rut" => QueryCollects::RutID(perid, viewer),
+        "user" => QueryCollects::UserID(perid, viewer, page),
+        _ => QueryCollects::RutID(perid, viewer),
     };
 
     db.send(collectIDs).from_err().and_then(|res| match res {
@@ -168,10 +176,18 @@ pub fn get_collect_list(
 pub fn get_collect(
     db: Data<DbAddr>,
     cid: Path<String>,
+    auth: Option<CheckUser>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     let collect_id = cid.into_inner();
     let action = "GET".to_string();
-    db.send(QueryCollect { collect_id, action })
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
+
+    db.send(QueryCollect {
+        collect_id,
+        action,
+        viewer,
+    })
         .from_err()
         .and_then(|res| match res {
             Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
@@ -202,10 +218,13 @@ pub fn update_collect(
     up_collect: Json<UpdateCollect>,
     auth: CheckUser,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
-    // need to check the auth_uname == collect_uname, on frontend??
-    // check id eque
+    let uname = auth.uname; // pass to handler to check permission
+    let up_c = UpdateCollect {
+        uname,
+        ..up_collect.into_inner()
+    };
 
-    db.send(up_collect.into_inner())
+    db.send(up_c)
         .from_err()
         .and_then(|res| match res {
             Ok(cmsg) => Ok(HttpResponse::Ok().json(cmsg)),
@@ -0,0 +1,36 @@
+// api.mention, view handler: @uname in etc, collect
+
+use actix_web::{
+    web::{self, Data, Path, Query},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::Future;
+
+use crate::api::PageQuery;
+use crate::model::mention::QueryMentions;
+use crate::model::user::CheckUser;
+use crate::DbAddr;
+
+// "/users/{uname}/mentions?page=" GET
+pub fn get_mentions(
+    db: Data<DbAddr>,
+    pq: Query<PageQuery>,
+    path_uname: Path<String>,
+    auth: Option<CheckUser>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let uname = path_uname.into_inner();
+    let page = std::cmp::max(pq.page, 1);
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
+
+    db.send(QueryMentions {
+        uname,
+        page,
+        viewer,
+    })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
@@ -8,10 +8,21 @@
 // actor: db, typed model,  msg handler
 
 pub mod auth;
+pub mod collab;
+pub mod digest;
 pub mod etc;
+pub mod feed;
 pub mod item;
+pub mod mention;
+pub mod notify;
+pub mod recommend;
 pub mod rut;
+pub mod search;
+pub mod suggest;
 pub mod tag;
+pub mod vote;
+pub mod webhook;
+pub mod ws;
 
 // for extract typed request Query info: /path?page=&flag=&kw=&fr=
 #[derive(Deserialize, Clone)]
@@ -21,3 +32,80 @@ pub struct ReqQuery {
     kw: String, // keyword  Option<String>?
     fr: String, // from user|tag..
 }
+
+// for extract typed request Query info of paging only: /path?page=
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PageQuery {
+    page: i32,
+}
+
+// for extract typed request Query info of index: /path?page=&window=week|month|all
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct IndexQuery {
+    page: i32,
+    window: String,
+}
+
+// for extract typed request Query info of suggestion: /path?page=&status=pending|accepted|declined
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SuggestQuery {
+    page: i32,
+    status: String,
+}
+
+// for extract typed request Query info of notification: /path?page=&flag=unread|all
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct NotifyQuery {
+    page: i32,
+    flag: String,
+}
+
+// for extract jwt in Query of ws, as browser cannot set header: /ws?token=
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WsQuery {
+    token: String,
+}
+
+// for extract typed request Query info of feed: /path?format=atom|rss
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct FeedQuery {
+    format: String,
+}
+
+// for extract typed request Query info of search: /search?q=&type=&tag=&user=&category=&page=
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SearchQuery {
+    q: String,
+    #[serde(rename = "type")]
+    kind: String, // rut|item|tag|user|etc, empty for all
+    tag: String,
+    user: String,
+    item: String,
+    category: String,
+    page: i32,
+}
+
+// for extract typed request Query info of etc list: /path?page=&flag=&kind=
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct EtcQuery {
+    page: i32,
+    flag: String, // sort: time|score|location
+    kind: String, // comment|excerpt|note|review, empty for all
+}
+
+// for extract typed request Query info of etc tree: /path?page=&depth=&sort=time|vote
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct TreeQuery {
+    page: i32,
+    depth: i32,
+    sort: String,
+}
@@ -0,0 +1,105 @@
+// api.notify, view handler: notification and preference
+
+use actix_web::{
+    web::{self, Data, Json, Path, Query},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::{future::result, Future};
+
+use crate::api::NotifyQuery;
+use crate::model::notify::{
+    CountNotifications, QueryNotifications, QueryNotifySettings, ReadNotification,
+    UpdateNotifySetting,
+};
+use crate::model::user::CheckUser;
+use crate::model::Validate;
+use crate::DbAddr;
+
+// "/notifications?page=&flag=unread|all" GET
+pub fn get_list(
+    db: Data<DbAddr>,
+    pq: Query<NotifyQuery>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let uname = auth.uname;
+    let page = std::cmp::max(pq.page, 1);
+    let unread = pq.flag.trim() == "unread";
+
+    db.send(QueryNotifications {
+        uname,
+        unread,
+        page,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
+
+// "/notifications/count" GET, unread count
+pub fn get_count(
+    db: Data<DbAddr>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let uname = auth.uname;
+
+    db.send(CountNotifications { uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/notifications/{nid}/{flag:read|unread}" POST
+pub fn mark_read(
+    db: Data<DbAddr>,
+    n_info: Path<(String, String)>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let nid = n_info.clone().0;
+    let read = n_info.1 == "read";
+    let uname = auth.uname;
+
+    db.send(ReadNotification { uname, nid, read })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/notifysettings" GET
+pub fn get_settings(
+    db: Data<DbAddr>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let uname = auth.uname;
+
+    db.send(QueryNotifySettings { uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/notifysettings" PUT
+pub fn update_setting(
+    db: Data<DbAddr>,
+    setting: Json<UpdateNotifySetting>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let up_setting = setting.into_inner();
+    let uname = auth.uname;
+    let new_setting = UpdateNotifySetting { uname, ..up_setting };
+
+    result(new_setting.validate())
+        .from_err()
+        .and_then(move |_| db.send(new_setting).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
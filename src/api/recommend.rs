@@ -0,0 +1,44 @@
+// api.recommend, view handler: similar items, recommendations
+
+use actix_web::{
+    web::{self, Data, Path, Query},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::Future;
+
+use crate::api::PageQuery;
+use crate::model::recommend::{QueryRecommendations, QuerySimilarItems};
+use crate::model::user::CheckUser;
+use crate::DbAddr;
+
+// "/similaritems/{itemid}" GET
+pub fn get_similar(
+    db: Data<DbAddr>,
+    itemid: Path<String>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let item_id = itemid.into_inner();
+
+    db.send(QuerySimilarItems { item_id })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/recommendations?page=" GET, for the caller
+pub fn get_recommendations(
+    db: Data<DbAddr>,
+    pq: Query<PageQuery>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let uname = auth.uname;
+    let page = std::cmp::max(pq.page, 1);
+
+    db.send(QueryRecommendations { uname, page })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
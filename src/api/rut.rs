@@ -6,12 +6,13 @@ use actix_web::{
 };
 use futures::{future::result, Future};
 
-use crate::api::ReqQuery;
+use crate::api::{IndexQuery, ReqQuery};
 use crate::model::{
-    rut::{CreateRut, QueryRut, QueryRuts, StarOrRut, StarRutStatus, UpdateRut},
+    rut::{CreateRut, QueryRelatedRuts, QueryRut, QueryRuts, StarOrRut, StarRutStatus, UpdateRut},
     user::CheckUser,
     Validate,
 };
+use crate::score::INDEX_MODES;
 use crate::DbAddr;
 
 // "/ruts" POST
@@ -35,9 +36,12 @@ pub fn new(
 pub fn get(
     r_slug: Path<String>,
     db: Data<DbAddr>,
+    auth: Option<CheckUser>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     let rut_slug = r_slug.into_inner();
-    db.send(QueryRut { rut_slug })
+    // who view, per visibility of rut
+    let uname = auth.map(|a| a.uname).unwrap_or_default();
+    db.send(QueryRut { rut_slug, uname })
         .from_err()
         .and_then(|res| match res {
             Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
@@ -50,6 +54,7 @@ pub fn get_list(
     db: Data<DbAddr>,
     pq: Query<ReqQuery>,
     per_info: Path<(String, String)>,
+    auth: Option<CheckUser>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     // extract Path
     let per = per_info.0.trim();
@@ -59,13 +64,15 @@ pub fn get_list(
     let flag = pq.clone().flag;
     let kw = pq.clone().kw;
     let fr = pq.clone().fr;
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
 
     let query_msg = match per {
         "item" => QueryRuts::ItemID(perid, page),
-        "tag" => QueryRuts::TagID(perid, page),
-        "user" => QueryRuts::UserID(perid, flag, page), // flag=create|star
+        "tag" => QueryRuts::TagID(perid, flag, page),  // flag=count|score
+        "user" => QueryRuts::UserID(perid, flag, viewer, page), // flag=create|star
         "key" => QueryRuts::KeyID(kw, fr, perid, page), // &kw=&fr=tag|user|item
-        _ => QueryRuts::Index(String::from("index")),
+        _ => QueryRuts::Index(String::from("new"), String::new(), page),
     };
 
     db.send(query_msg).from_err().and_then(|res| match res {
@@ -74,12 +81,38 @@ pub fn get_list(
     })
 }
 
+// "/ruts/index/{mode}?page=p&window=week|month|all" GET, mode: new|hot|top, others as new
+pub fn get_index(
+    db: Data<DbAddr>,
+    iq: Query<IndexQuery>,
+    mode: Path<String>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    // unknown or legacy mode, e.g. index, as new
+    let mode = match mode.trim() {
+        m if INDEX_MODES.contains(&m) => m.to_owned(),
+        _ => String::from("new"),
+    };
+    let page = std::cmp::max(iq.page, 1);
+    let window = iq.clone().window;
+
+    db.send(QueryRuts::Index(mode, window, page))
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
 pub fn update(
     db: Data<DbAddr>,
     rut: Json<UpdateRut>,
     auth: CheckUser,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
-    let up_rut = rut.into_inner();
+    let uname = auth.uname; // pass to handler to check permission
+    let up_rut = UpdateRut {
+        uname,
+        ..rut.into_inner()
+    };
 
     result(up_rut.validate())
         .from_err()
@@ -129,5 +162,23 @@ pub fn star_status(
         })
 }
 
+// "/relatedruts/{rutid}" GET, per shared items and tags, w/ near-duplicate if any
+pub fn get_related(
+    db: Data<DbAddr>,
+    rutid: Path<String>,
+    auth: Option<CheckUser>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = rutid.into_inner();
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
+
+    db.send(QueryRelatedRuts { rut_id, viewer })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
 // todo
 // pub fn delete() {}
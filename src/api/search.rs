@@ -0,0 +1,40 @@
+// api.search, view handler: search across ruts, items, tags, users, etcs
+
+use actix_web::{
+    web::{self, Data, Path, Query},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::{future::result, Future};
+
+use crate::api::SearchQuery;
+use crate::model::search::{Search, SearchScope};
+use crate::model::Validate;
+use crate::DbAddr;
+
+// "/search?q=&type=&tag=&user=&item=&category=&page=" GET
+// type empty: top hits per type, grouped, page ignored
+pub fn search(
+    db: Data<DbAddr>,
+    sq: Query<SearchQuery>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let sq = sq.into_inner();
+    let search = Search {
+        q: sq.q,
+        kind: sq.kind.trim().to_lowercase(),
+        scope: SearchScope {
+            tag: sq.tag,
+            user: sq.user,
+            item: sq.item,
+            category: sq.category,
+        },
+        page: std::cmp::max(sq.page, 1),
+    };
+
+    result(search.validate())
+        .from_err()
+        .and_then(move |_| db.send(search).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
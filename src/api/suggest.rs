@@ -0,0 +1,101 @@
+// api.suggest, view handler: suggest an item to others' rut
+
+use actix_web::{
+    web::{Data, Json, Path, Query},
+    Error, HttpResponse, ResponseError,
+};
+use futures::{future::result, Future};
+
+use crate::api::SuggestQuery;
+use crate::model::suggest::{AcceptSuggest, DeclineSuggest, NewSuggest, QuerySuggests};
+use crate::model::user::CheckUser;
+use crate::model::Validate;
+use crate::DbAddr;
+
+// "/rutsuggests/{rutid}" POST, {item_id, note}
+pub fn new(
+    db: Data<DbAddr>,
+    rid: Path<String>,
+    ns: Json<NewSuggest>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = rid.into_inner();
+    let uname = auth.uname;
+    let new_suggest = NewSuggest {
+        rut_id,
+        uname,
+        ..ns.into_inner()
+    };
+
+    result(new_suggest.validate())
+        .from_err()
+        .and_then(move |_| db.send(new_suggest).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
+
+// "/rutsuggests/{rutid}?page=&status=pending|accepted|declined" GET, to owner|editor
+pub fn get_list(
+    db: Data<DbAddr>,
+    sq: Query<SuggestQuery>,
+    rid: Path<String>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let query_msg = QuerySuggests {
+        rut_id: rid.into_inner(),
+        status: sq.clone().status,
+        uname: auth.uname,
+        page: std::cmp::max(sq.page, 1),
+    };
+
+    result(query_msg.validate())
+        .from_err()
+        .and_then(move |_| db.send(query_msg).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
+
+// "/suggests/{sid}/accept" POST, collect the item per suggestion
+pub fn accept(
+    db: Data<DbAddr>,
+    sid: Path<String>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let suggest_id = sid.into_inner();
+    let uname = auth.uname; // pass to handler to check permission
+
+    db.send(AcceptSuggest { suggest_id, uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/suggests/{sid}/decline" POST, {reason}
+pub fn decline(
+    db: Data<DbAddr>,
+    sid: Path<String>,
+    ds: Json<DeclineSuggest>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let suggest_id = sid.into_inner();
+    let uname = auth.uname; // pass to handler to check permission
+    let decline = DeclineSuggest {
+        suggest_id,
+        uname,
+        ..ds.into_inner()
+    };
+
+    result(decline.validate())
+        .from_err()
+        .and_then(move |_| db.send(decline).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
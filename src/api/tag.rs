@@ -8,8 +8,8 @@ use futures::{future::result, Future};
 
 use crate::api::ReqQuery;
 use crate::model::tag::{
-    CheckTag, QueryTags, RutTag, StarOrTag, 
-    StarTagStatus, Tag, TagAny, TagRut, UpdateTag,
+    CheckTag, ModerateRutTag, QueryRelatedTags, QueryTagVotes, QueryTags, RutTag, 
+    StarOrTag, StarTagStatus, SuggestTags, Tag, TagAny, TagRut, UpdateTag,
 };
 use crate::model::user::CheckUser;
 use crate::model::{replace_sep, Validate, TAG_LEN};
@@ -51,13 +51,16 @@ pub fn get(db: Data<DbAddr>, tg: Path<String>) -> impl Future<Item = HttpRespons
 pub fn get_list(
     db: Data<DbAddr>,
     per_info: Path<(String, String)>,
+    auth: Option<CheckUser>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     // extract Path
     let per = per_info.0.trim();
     let perid = per_info.clone().1;
+    // who view, per visibility of rut
+    let viewer = auth.map(|a| a.uname).unwrap_or_default();
 
     let tg_msg = match per {
-        "rut" => QueryTags::RutID(perid),
+        "rut" => QueryTags::RutID(perid, viewer),
         "item" => QueryTags::ItemID(perid),
         "tag" => QueryTags::TagID(perid),
         "user" => QueryTags::UserID(perid),
@@ -70,6 +73,51 @@ pub fn get_list(
     })
 }
 
+// "/relatedtags/{tname}" GET
+pub fn get_related(
+    db: Data<DbAddr>,
+    tg: Path<String>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let tname = tg.into_inner();
+
+    db.send(QueryRelatedTags { tname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/suggesttags/item/{id}" GET
+pub fn suggest_item_tags(
+    db: Data<DbAddr>,
+    itemid: Path<String>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let item_id = itemid.into_inner();
+
+    db.send(SuggestTags::ItemID(item_id))
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/suggesttags/rut/{id}" GET
+pub fn suggest_rut_tags(
+    db: Data<DbAddr>,
+    rutid: Path<String>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = rutid.into_inner();
+
+    db.send(SuggestTags::RutID(rut_id))
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
 pub fn update(
     db: Data<DbAddr>,
     tg: Json<UpdateTag>,
@@ -112,7 +160,12 @@ pub fn tag_rut(
         .filter(|t| t.len() <= TAG_LEN && t.len() >= 1)
         .collect();
 
-    let rut_tags = RutTag { tnames, ..tags };
+    let uname = auth.uname;
+    let rut_tags = RutTag {
+        tnames,
+        uname,
+        ..tags
+    };
 
     result(rut_tags.validate())
         .from_err()
@@ -140,7 +193,12 @@ pub fn tag_any(
         .filter(|t| t.len() <= TAG_LEN && t.len() >= 1)
         .collect();
 
-    let any_tags = TagAny { tnames, ..tags };
+    let uname = auth.uname;
+    let any_tags = TagAny {
+        tnames,
+        uname,
+        ..tags
+    };
 
     result(any_tags.validate())
         .from_err()
@@ -151,6 +209,52 @@ pub fn tag_any(
         })
 }
 
+// "/ruttags/{rutid}/{tname}/{action}" POST, action: lock|unlock|remove
+pub fn moderate_rut_tag(
+    db: Data<DbAddr>,
+    md_info: Path<(String, String, String)>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let rut_id = md_info.clone().0;
+    let tname = md_info.clone().1;
+    let action = md_info.clone().2;
+    let uname = auth.uname;
+
+    db.send(ModerateRutTag {
+        rut_id,
+        tname,
+        action,
+        uname,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
+
+// "/iftagvote/{vote_to}/{to_id}" GET, the tags voted by auth user
+pub fn tag_vote_status(
+    db: Data<DbAddr>,
+    vt_info: Path<(String, String)>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let vote_to = vt_info.clone().0;
+    let to_id = vt_info.clone().1;
+    let uname = auth.uname;
+
+    db.send(QueryTagVotes {
+        uname,
+        vote_to,
+        to_id,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
+
 pub fn star_or_unstar(
     db: Data<DbAddr>,
     star_info: Path<(String, u8, String)>,
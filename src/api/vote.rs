@@ -0,0 +1,61 @@
+// api.vote, view handler: up|down vote etc, rut, item
+
+use actix_web::{
+    web::{self, Data, Json, Path, Query},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::{future::result, Future};
+
+use crate::model::user::CheckUser;
+use crate::model::vote::{VoteFor, VoteStatus};
+use crate::model::Validate;
+use crate::DbAddr;
+
+// "/vote/{vote_to}/{to_id}/{updown}" POST, updown: 1|-1|0
+pub fn vote(
+    db: Data<DbAddr>,
+    vote_info: Path<(String, String, i16)>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let vote_to = vote_info.clone().0;
+    let to_id = vote_info.clone().1;
+    let updown = vote_info.2;
+    let uname = auth.uname;
+
+    let vote_for = VoteFor {
+        uname,
+        vote_to,
+        to_id,
+        updown,
+    };
+
+    result(vote_for.validate())
+        .from_err()
+        .and_then(move |_| db.send(vote_for).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
+
+// "/ifvote/{vote_to}/{to_id}" GET
+pub fn vote_status(
+    db: Data<DbAddr>,
+    vote_info: Path<(String, String)>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let vote_to = vote_info.clone().0;
+    let to_id = vote_info.clone().1;
+    let uname = auth.uname;
+
+    db.send(VoteStatus {
+        uname,
+        vote_to,
+        to_id,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
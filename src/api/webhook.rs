@@ -0,0 +1,106 @@
+// api.webhook, view handler: outgoing webhook for integration
+
+use actix_web::{
+    web::{self, Data, Json, Path, Query},
+    Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use futures::{future::result, Future};
+
+use crate::api::PageQuery;
+use crate::model::user::CheckUser;
+use crate::model::webhook::{DelWebhook, NewWebhook, QueryDeliveries, QueryWebhooks, TestWebhook};
+use crate::model::Validate;
+use crate::task::deliver::DeliverAddr;
+use crate::DbAddr;
+
+// "/webhooks" POST
+pub fn new(
+    db: Data<DbAddr>,
+    nw: Json<NewWebhook>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let new_w = nw.into_inner();
+    let uname = auth.uname;
+    let new_webhook = NewWebhook { uname, ..new_w };
+
+    result(new_webhook.validate())
+        .from_err()
+        .and_then(move |_| db.send(new_webhook).from_err())
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(e) => Ok(e.error_response()),
+        })
+}
+
+// "/webhooks" GET
+pub fn get_list(
+    db: Data<DbAddr>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let uname = auth.uname;
+
+    db.send(QueryWebhooks { uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/webhooks/{wid}" DELETE
+pub fn delete(
+    db: Data<DbAddr>,
+    wid: Path<String>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let webhook_id = wid.into_inner();
+    let uname = auth.uname;
+
+    db.send(DelWebhook { webhook_id, uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
+
+// "/webhooks/{wid}/deliveries?page=" GET
+pub fn get_deliveries(
+    db: Data<DbAddr>,
+    pq: Query<PageQuery>,
+    wid: Path<String>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let webhook_id = wid.into_inner();
+    let uname = auth.uname;
+    let page = std::cmp::max(pq.page, 1);
+
+    db.send(QueryDeliveries {
+        webhook_id,
+        uname,
+        page,
+    })
+    .from_err()
+    .and_then(|res| match res {
+        Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+        Err(err) => Ok(err.error_response()),
+    })
+}
+
+// "/webhooks/{wid}/test" POST, deliver a ping at once
+pub fn test(
+    deliver: Data<DeliverAddr>,
+    wid: Path<String>,
+    auth: CheckUser,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let webhook_id = wid.into_inner();
+    let uname = auth.uname;
+
+    deliver
+        .send(TestWebhook { webhook_id, uname })
+        .from_err()
+        .and_then(|res| match res {
+            Ok(msg) => Ok(HttpResponse::Ok().json(msg)),
+            Err(err) => Ok(err.error_response()),
+        })
+}
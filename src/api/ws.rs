@@ -0,0 +1,34 @@
+// api.ws, view handler: websocket for real-time updates
+
+use actix_web::{
+    web::{self, Data, Payload, Query},
+    Error, HttpRequest, HttpResponse,
+};
+use actix_web_actors::ws;
+
+use crate::api::WsQuery;
+use crate::errors::ServiceError;
+use crate::hub::session::WsSession;
+use crate::model::user::decode_token;
+use crate::DbAddr;
+
+// "/ws?token=" GET, auth with jwt as CheckUser
+pub fn ws_index(
+    req: HttpRequest,
+    db: Data<DbAddr>,
+    wq: Query<WsQuery>,
+    stream: Payload,
+) -> Result<HttpResponse, Error> {
+    let token = if wq.token.trim() != "" {
+        wq.token.clone()
+    } else {
+        req.headers()
+            .get("authorization")
+            .and_then(|t| t.to_str().ok())
+            .unwrap_or("")
+            .to_owned()
+    };
+    let user = decode_token(&token).map_err(|_| ServiceError::Unauthorized)?;
+
+    ws::start(WsSession::new(user.uname, db.get_ref().clone()), &req, stream)
+}
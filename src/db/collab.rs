@@ -0,0 +1,259 @@
+// collaborator msg handler, and the permission check per role
+
+use actix::Handler;
+use chrono::Utc;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::db::notify::notify;
+use crate::errors::ServiceError;
+use crate::model::collab::{
+    AcceptCollab, CheckView, InviteCollab, QueryCollabs, RemoveCollab, RutCollab,
+};
+use crate::model::msg::{CollabListMsg, CollabMsg, Msg};
+use crate::model::rut::Rut;
+use crate::Dba;
+
+// the role of user on rut if accepted
+pub fn collab_role(
+    conn: &PgConnection,
+    rutid: &str,
+    who: &str,
+) -> Result<Option<String>, ServiceError> {
+    use crate::schema::rutcollabs::dsl::*;
+    let r = rutcollabs
+        .filter(&rut_id.eq(rutid))
+        .filter(&uname.eq(who))
+        .filter(&accepted.eq(true))
+        .select(role)
+        .load::<String>(conn)?
+        .pop();
+    Ok(r)
+}
+
+// owner or editor can collect, edit the collects and update the rut
+pub fn can_edit(conn: &PgConnection, r: &Rut, who: &str) -> Result<bool, ServiceError> {
+    if who == "" {
+        return Ok(false);
+    }
+    if r.uname == who {
+        return Ok(true);
+    }
+    Ok(collab_role(conn, &r.id, who)? == Some("editor".to_owned()))
+}
+
+// private rut to owner and collaborators, of any role
+pub fn can_view(conn: &PgConnection, r: &Rut, who: &str) -> Result<bool, ServiceError> {
+    if r.can_view(who) {
+        return Ok(true);
+    }
+    if who == "" {
+        return Ok(false);
+    }
+    Ok(collab_role(conn, &r.id, who)?.is_some())
+}
+
+fn get_rut(conn: &PgConnection, rutid: &str) -> Result<Rut, ServiceError> {
+    use crate::schema::ruts::dsl::*;
+    let r = ruts.filter(&id.eq(rutid)).get_result::<Rut>(conn)?;
+    Ok(r)
+}
+
+// load the rut if who can view it, otherwise as not found
+pub fn viewable_rut(conn: &PgConnection, rutid: &str, who: &str) -> Result<Rut, ServiceError> {
+    let r = get_rut(conn, rutid)?;
+    if !can_view(conn, &r, who)? {
+        return Err(ServiceError::NotFound("Not Found".into()));
+    }
+    Ok(r)
+}
+
+// handle msg from api::collab.invite
+impl Handler<InviteCollab> for Dba {
+    type Result = Result<CollabMsg, ServiceError>;
+
+    fn handle(&mut self, inv: InviteCollab, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutcollabs::dsl::*;
+        let conn = &self.0.get()?;
+
+        let rut_q = get_rut(conn, &inv.rut_id)?;
+        // only owner can invite
+        if rut_q.uname != inv.invite_by {
+            return Err(ServiceError::Unauthorized);
+        }
+        if inv.uname == rut_q.uname {
+            return Err(ServiceError::BadRequest("400: Owner already".into()));
+        }
+        // check if the user existing
+        {
+            use crate::schema::users::dsl::{uname as u_name, users};
+            let u_num: i64 = users.filter(&u_name.eq(&inv.uname)).count().get_result(conn)?;
+            if u_num == 0 {
+                return Err(ServiceError::NotFound("No Such User".into()));
+            }
+        }
+
+        let check_collab = rutcollabs
+            .filter(&rut_id.eq(&inv.rut_id))
+            .filter(&uname.eq(&inv.uname))
+            .load::<RutCollab>(conn)?
+            .pop();
+        let collab = match check_collab {
+            // invited already, just change the role
+            Some(c) => diesel::update(&c)
+                .set(role.eq(inv.role.trim()))
+                .get_result::<RutCollab>(conn)?,
+            None => {
+                let new_collab = RutCollab {
+                    id: format!("{}", uuid::Uuid::new_v4()),
+                    rut_id: inv.rut_id.clone(),
+                    uname: inv.uname.clone(),
+                    role: inv.role.trim().to_owned(),
+                    accepted: false,
+                    invite_by: inv.invite_by.clone(),
+                    invite_at: Utc::now().naive_utc(),
+                    accept_at: None,
+                };
+                let c = diesel::insert_into(rutcollabs)
+                    .values(&new_collab)
+                    .get_result::<RutCollab>(conn)?;
+                notify(
+                    conn,
+                    &c.uname,
+                    &c.invite_by,
+                    "invite_collab",
+                    "rut",
+                    &c.rut_id,
+                    &rut_q.title,
+                )?;
+                c
+            }
+        };
+
+        Ok(CollabMsg {
+            status: 201,
+            message: "Invited".to_string(),
+            collab,
+        })
+    }
+}
+
+// handle msg from api::collab.accept
+impl Handler<AcceptCollab> for Dba {
+    type Result = Result<CollabMsg, ServiceError>;
+
+    fn handle(&mut self, ac: AcceptCollab, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutcollabs::dsl::*;
+        let conn = &self.0.get()?;
+
+        let c = rutcollabs
+            .filter(&rut_id.eq(&ac.rut_id))
+            .filter(&uname.eq(&ac.uname))
+            .get_result::<RutCollab>(conn)?;
+        if c.accepted {
+            return Ok(CollabMsg {
+                status: 200,
+                message: "Accepted".to_string(),
+                collab: c,
+            });
+        }
+
+        let collab = diesel::update(&c)
+            .set((accepted.eq(true), accept_at.eq(Some(Utc::now().naive_utc()))))
+            .get_result::<RutCollab>(conn)?;
+        let rut_q = get_rut(conn, &collab.rut_id)?;
+        notify(
+            conn,
+            &rut_q.uname,
+            &collab.uname,
+            "accept_collab",
+            "rut",
+            &collab.rut_id,
+            &rut_q.title,
+        )?;
+
+        Ok(CollabMsg {
+            status: 201,
+            message: "Accepted".to_string(),
+            collab,
+        })
+    }
+}
+
+// handle msg from api::collab.remove
+impl Handler<RemoveCollab> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, rc: RemoveCollab, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutcollabs::dsl::*;
+        let conn = &self.0.get()?;
+
+        // check permission: owner, or leave|decline by self
+        if rc.action_by != rc.uname {
+            let rut_q = get_rut(conn, &rc.rut_id)?;
+            if rut_q.uname != rc.action_by {
+                return Err(ServiceError::Unauthorized);
+            }
+        }
+
+        let num = diesel::delete(
+            rutcollabs
+                .filter(&rut_id.eq(&rc.rut_id))
+                .filter(&uname.eq(&rc.uname)),
+        )
+        .execute(conn)?;
+        if num == 0 {
+            return Err(ServiceError::NotFound("Not Found".into()));
+        }
+
+        Ok(Msg {
+            status: 204,
+            message: "Removed".to_string(),
+        })
+    }
+}
+
+// handle msg from api::collab.get_list
+impl Handler<QueryCollabs> for Dba {
+    type Result = Result<CollabListMsg, ServiceError>;
+
+    fn handle(&mut self, qc: QueryCollabs, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutcollabs::dsl::*;
+        let conn = &self.0.get()?;
+
+        let rut_q = get_rut(conn, &qc.rut_id)?;
+        if !can_view(conn, &rut_q, &qc.viewer)? {
+            return Err(ServiceError::NotFound("Not Found".into()));
+        }
+        // pending invitations to owner only
+        let query = rutcollabs.filter(&rut_id.eq(&qc.rut_id));
+        let collab_list = if rut_q.uname == qc.viewer {
+            query.order(invite_at.asc()).load::<RutCollab>(conn)?
+        } else {
+            query
+                .filter(&accepted.eq(true))
+                .order(invite_at.asc())
+                .load::<RutCollab>(conn)?
+        };
+
+        Ok(CollabListMsg {
+            status: 200,
+            message: "Success".to_string(),
+            count: collab_list.len(),
+            collabs: collab_list,
+        })
+    }
+}
+
+// handle msg from hub::session, check before subscribe
+impl Handler<CheckView> for Dba {
+    type Result = Result<bool, ServiceError>;
+
+    fn handle(&mut self, cv: CheckView, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+
+        let rut_q = get_rut(conn, &cv.rut_id)?;
+        can_view(conn, &rut_q, &cv.uname)
+    }
+}
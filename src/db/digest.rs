@@ -0,0 +1,203 @@
+// digest typed model and msg handler
+
+use actix::Handler;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::{self, dsl::any, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::db::collab::can_view;
+use crate::errors::ServiceError;
+use crate::model::digest::{Digest, DigestSetting, QueryDigestSetting, UpdateDigestSetting};
+use crate::model::etc::Etc;
+use crate::model::item::{Collect, Item};
+use crate::model::msg::DigestSettingMsg;
+use crate::model::rut::Rut;
+use crate::Dba;
+
+// max entries per section in digest
+const DIGEST_LIMIT: i64 = 20;
+
+// handle msg from api::digest.get_setting
+impl Handler<QueryDigestSetting> for Dba {
+    type Result = Result<DigestSettingMsg, ServiceError>;
+
+    fn handle(&mut self, qd: QueryDigestSetting, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+
+        Ok(DigestSettingMsg {
+            status: 200,
+            message: "Get".to_string(),
+            setting: get_setting(conn, &qd.uname)?,
+        })
+    }
+}
+
+// handle msg from api::digest.update_setting
+impl Handler<UpdateDigestSetting> for Dba {
+    type Result = Result<DigestSettingMsg, ServiceError>;
+
+    fn handle(&mut self, ud: UpdateDigestSetting, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::digestsettings::dsl::*;
+        let conn = &self.0.get()?;
+
+        let old = get_setting(conn, &ud.uname)?;
+        let setting = if old.id == "" {
+            let new_setting = DigestSetting {
+                id: format!("{}", uuid::Uuid::new_v4()),
+                frequency: ud.frequency.trim().to_owned(),
+                ..old
+            };
+            diesel::insert_into(digestsettings)
+                .values(&new_setting)
+                .get_result::<DigestSetting>(conn)?
+        } else {
+            diesel::update(&old)
+                .set(frequency.eq(ud.frequency.trim()))
+                .get_result::<DigestSetting>(conn)?
+        };
+
+        Ok(DigestSettingMsg {
+            status: 200,
+            message: "Updated".to_string(),
+            setting,
+        })
+    }
+}
+
+// preference of user, off if no record, as digest is opt in
+pub fn get_setting(conn: &PgConnection, u_name: &str) -> Result<DigestSetting, ServiceError> {
+    use crate::schema::digestsettings::dsl::*;
+
+    let setting = digestsettings
+        .filter(&uname.eq(u_name))
+        .load::<DigestSetting>(conn)?
+        .pop()
+        .unwrap_or_else(|| DigestSetting {
+            id: "".to_owned(),
+            uname: u_name.to_owned(),
+            frequency: "off".to_owned(),
+            last_sent: None,
+        });
+
+    Ok(setting)
+}
+
+// collect new activity since the last digest:
+// new ruts in starred tags, new items in starred ruts, replies to user's etcs
+pub fn collect_digest(
+    conn: &PgConnection,
+    u_name: &str,
+    since: NaiveDateTime,
+) -> Result<Digest, ServiceError> {
+    // new ruts in starred tags
+    let tag_ruts = {
+        use crate::schema::ruts::dsl::{
+            create_at, id as rid, ruts, uname as r_uname, visibility,
+        };
+        use crate::schema::startags::dsl::{startags, tname as st_name, uname as st_uname};
+        use crate::schema::tagruts::dsl::{rut_id as tr_rut_id, tagruts, tname as tr_name};
+
+        let tnames = startags
+            .filter(&st_uname.eq(u_name))
+            .select(st_name)
+            .load::<String>(conn)?;
+        if tnames.len() > 0 {
+            ruts.filter(
+                rid.eq_any(
+                    tagruts
+                        .filter(tr_name.eq(any(tnames)))
+                        .select(tr_rut_id),
+                ),
+            )
+            .filter(&create_at.gt(since))
+            .filter(&r_uname.ne(u_name))
+            .filter(visibility.eq("public"))
+            .order(create_at.desc())
+            .limit(DIGEST_LIMIT)
+            .load::<Rut>(conn)?
+        } else {
+            Vec::new()
+        }
+    };
+
+    // new items in starred ruts
+    let rut_items = {
+        use crate::schema::collects::dsl::{collect_at, collects, rut_id as c_rut_id};
+        use crate::schema::starruts::dsl::{rut_id as sr_rut_id, starruts, uname as sr_uname};
+
+        use crate::schema::ruts::dsl::{id as rid, ruts};
+
+        // the starred may be private since, to who can view only
+        let starred = ruts
+            .filter(
+                rid.eq_any(
+                    starruts
+                        .filter(&sr_uname.eq(u_name))
+                        .select(sr_rut_id),
+                ),
+            )
+            .load::<Rut>(conn)?;
+        let mut rut_ids: Vec<String> = Vec::new();
+        for r in starred.iter() {
+            if can_view(conn, r, u_name)? {
+                rut_ids.push(r.id.clone());
+            }
+        }
+        let collect_list = if rut_ids.len() > 0 {
+            collects
+                .filter(&c_rut_id.eq(any(&rut_ids)))
+                .filter(&collect_at.gt(since))
+                .order(collect_at.desc())
+                .limit(DIGEST_LIMIT)
+                .load::<Collect>(conn)?
+        } else {
+            Vec::new()
+        };
+
+        let mut pairs: Vec<(Rut, Item)> = Vec::new();
+        if collect_list.len() > 0 {
+            use crate::schema::items::dsl::{id as iid, items};
+            use crate::schema::ruts::dsl::{id as rid, ruts};
+            let c_rut_ids: Vec<String> = collect_list.iter().map(|c| c.rut_id.clone()).collect();
+            let c_item_ids: Vec<String> = collect_list.iter().map(|c| c.item_id.clone()).collect();
+            let rut_list = ruts.filter(&rid.eq(any(c_rut_ids))).load::<Rut>(conn)?;
+            let item_list = items.filter(&iid.eq(any(c_item_ids))).load::<Item>(conn)?;
+            for c in collect_list.iter() {
+                let r = rut_list.iter().find(|r| r.id == c.rut_id);
+                let i = item_list.iter().find(|i| i.id == c.item_id);
+                if let (Some(r), Some(i)) = (r, i) {
+                    pairs.push((r.clone(), i.clone()));
+                }
+            }
+        }
+        pairs
+    };
+
+    // replies to user's etcs
+    let replies = {
+        use crate::schema::etcs::dsl::*;
+
+        let p_ids = etcs
+            .filter(&uname.eq(u_name))
+            .filter(&reply_count.gt(0))
+            .select(id)
+            .load::<String>(conn)?;
+        if p_ids.len() > 0 {
+            etcs.filter(&petc_id.eq(any(&p_ids)))
+                .filter(&post_at.gt(since))
+                .filter(&uname.ne(u_name))
+                .filter(&deleted.eq(false))
+                .order(post_at.desc())
+                .limit(DIGEST_LIMIT)
+                .load::<Etc>(conn)?
+        } else {
+            Vec::new()
+        }
+    };
+
+    Ok(Digest {
+        tag_ruts,
+        rut_items,
+        replies,
+    })
+}
@@ -7,12 +7,23 @@ use diesel::{
     self, dsl::any, ExpressionMethods, 
     PgTextExpressionMethods, QueryDsl, RunQueryDsl
 };
+use diesel::pg::{Pg, PgConnection};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::db::collab::viewable_rut;
+use crate::db::mention::{del_mentions, save_mentions};
+use crate::db::notify::notify;
+use crate::db::webhook::enqueue;
 use crate::errors::ServiceError;
-use crate::model::etc::{Etc, PostEtc, QueryEtcs};
-use crate::model::msg::{EtcListMsg, EtcMsg, Msg};
+use crate::hub::publish;
+use crate::model::etc::{DelEtc, Etc, EtcNode, PostEtc, QueryEtcTree, QueryEtcs, UpdateEtc};
+use crate::model::msg::{EtcListMsg, EtcMsg, EtcTreeMsg, Msg};
+use crate::model::rut::Rut;
 use crate::model::PER_PAGE;
+use crate::score::{vote_item, vote_rut, vote_tags};
+use crate::search::{index_doc, remove_doc, Doc};
+use crate::util::md::md_to_html;
 use crate::Dba;
 
 // handle msg from api::etc.post_etc
@@ -30,8 +41,9 @@ impl Handler<PostEtc> for Dba {
         id_map.insert(new_etc.post_to.clone(), new_etc.to_id.clone());
 
         let uid = format!("{}", uuid::Uuid::new_v4());
-        let newetc = Etc {
+        let mut newetc = Etc {
             id: uid,
+            content_html: md_to_html(&new_etc.content),
             content: new_etc.content,
             post_at: Utc::now().naive_utc(),
             petc_id: get_v(&id_map, "petc"),
@@ -39,18 +51,116 @@ impl Handler<PostEtc> for Dba {
             item_id: get_v(&id_map, "item"),
             tname: get_v(&id_map, "tag"),
             uname: new_etc.uname,
-            vote: 1,
+            vote: 0,
+            reply_count: 0,
+            edited_at: None,
+            deleted: false,
+            kind: new_etc.kind.trim().to_owned(),
+            page: new_etc.page,
+            chapter: new_etc.chapter.trim().to_owned(),
+            location: new_etc.location.trim().to_owned(),
+            percent: new_etc.percent,
         };
+        // a reply inherit rut|item|tag of parent, to be in the thread
+        if &new_etc.post_to == "petc" {
+            let p_etc = etcs.filter(&id.eq(&new_etc.to_id)).get_result::<Etc>(conn)?;
+            if p_etc.deleted {
+                return Err(ServiceError::BadRequest("Deleted".into()));
+            }
+            newetc.rut_id = p_etc.rut_id;
+            newetc.item_id = p_etc.item_id;
+            newetc.tname = p_etc.tname;
+        }
+        // comment on private rut by who can view only
+        if &newetc.rut_id != "" {
+            viewable_rut(conn, &newetc.rut_id, &newetc.uname)?;
+        }
         let etc_new = diesel::insert_into(etcs)
             .values(&newetc)
             .get_result::<Etc>(conn)?;
+        let mentioned = save_mentions(
+            conn,
+            &etc_new.content,
+            &etc_new.uname,
+            "etc",
+            &etc_new.id,
+            &etc_new.rut_id,
+            &etc_new.item_id,
+        )?;
+        // notify once per user
+        let mut notified: Vec<String> = Vec::new();
+        let mut rut_owner = String::new();
 
+        // update reply_count + 1 in parent
+        if &etc_new.petc_id != "" {
+            let p_etc = diesel::update(etcs.filter(&id.eq(&etc_new.petc_id)))
+                .set(reply_count.eq(reply_count + 1))
+                .get_result::<Etc>(conn)?;
+            notify(
+                conn,
+                &p_etc.uname,
+                &etc_new.uname,
+                "reply_etc",
+                "etc",
+                &etc_new.id,
+                &etc_new.content,
+            )?;
+            notified.push(p_etc.uname);
+        }
         // update comment_count + 1 in ruts
-        if &new_etc.post_to == "rut" {
+        if &etc_new.rut_id != "" {
             use crate::schema::ruts::dsl::*;
-            diesel::update(ruts.filter(&id.eq(&new_etc.to_id)))
+            let rut_q = diesel::update(ruts.filter(&id.eq(&etc_new.rut_id)))
                 .set(comment_count.eq(comment_count + 1))
+                .get_result::<Rut>(conn)?;
+            vote_rut(conn, &rut_q.id)?;
+            if !notified.contains(&rut_q.uname) {
+                notify(
+                    conn,
+                    &rut_q.uname,
+                    &etc_new.uname,
+                    "comment_rut",
+                    "etc",
+                    &etc_new.id,
+                    &etc_new.content,
+                )?;
+                notified.push(rut_q.uname.clone());
+            }
+            rut_owner = rut_q.uname;
+        }
+        for m in mentioned.iter().filter(|m| !notified.contains(m)) {
+            notify(
+                conn,
+                m,
+                &etc_new.uname,
+                "mention",
+                "etc",
+                &etc_new.id,
+                &etc_new.content,
+            )?;
+        }
+        // update etc_count + 1 in items
+        if &etc_new.item_id != "" {
+            use crate::schema::items::dsl::*;
+            diesel::update(items.filter(&id.eq(&etc_new.item_id)))
+                .set(etc_count.eq(etc_count + 1))
                 .execute(conn)?;
+            vote_item(conn, &etc_new.item_id)?;
+        }
+
+        enqueue(
+            conn,
+            "etc_posted",
+            &[etc_new.uname.as_str(), rut_owner.as_str()],
+            &etc_new,
+        )?;
+        index_doc(conn, Doc::Etc(&etc_new));
+        // push to the subscribed
+        if &etc_new.rut_id != "" {
+            publish(format!("rut:{}", etc_new.rut_id), "etc", &etc_new);
+        }
+        if &etc_new.tname != "" {
+            publish(format!("tag:{}", etc_new.tname), "etc", &etc_new);
         }
 
         Ok(EtcMsg {
@@ -79,45 +189,46 @@ impl Handler<QueryEtcs> for Dba {
 
         let per_id = &per.perid;
         let per_to = per.per.trim();
+        check_per_view(conn, per_to, per_id, &per.viewer)?;
 
-        let etc_list = match per_to {
-            "rut" => etcs
-                .filter(&rut_id.eq(per_id))
-                .order(post_at.desc())
-                .limit(PER_PAGE.into())
-                .offset((PER_PAGE * (p - 1)).into())
-                .load::<Etc>(conn)?,
-            "item" => etcs
-                .filter(&item_id.eq(per_id))
-                .order(post_at.desc())
-                .limit(PER_PAGE.into())
-                .offset((PER_PAGE * (p - 1)).into())
-                .load::<Etc>(conn)?,
-            "tag" => etcs
-                .filter(&tname.eq(per_id))
-                .order(post_at.desc())
-                .limit(PER_PAGE.into())
-                .offset((PER_PAGE * (p - 1)).into())
-                .load::<Etc>(conn)?,
-            "petc" => etcs
-                .filter(&petc_id.eq(per_id))
-                .order(post_at.desc())
-                .limit(PER_PAGE.into())
-                .offset((PER_PAGE * (p - 1)).into())
-                .load::<Etc>(conn)?,
-            "user" => etcs
-                .filter(&uname.eq(per_id))
-                .order(post_at.desc())
-                .limit(PER_PAGE.into())
-                .offset((PER_PAGE * (p - 1)).into())
-                .load::<Etc>(conn)?,
-            _ => {
-                // just get some newest
-                etcs.order(post_at.desc())
-                    .limit(PER_PAGE.into())
-                    .load::<Etc>(conn)?
-            }
+        // not on rut, or in public ruts, or the ruts owned by viewer
+        use crate::schema::ruts::dsl::{id as rid, ruts, uname as r_uname, visibility};
+        let listed = ruts
+            .filter(visibility.eq("public").or(r_uname.eq(&per.viewer)))
+            .select(rid);
+        let query = etcs.filter(&deleted.eq(false)).into_boxed();
+        // flat list of rut|item|tag: top level only, as the replies inherit rut|item|tag
+        let query = match per_to {
+            "rut" => query.filter(rut_id.eq(per_id)).filter(petc_id.eq("")),
+            "petc" => query.filter(petc_id.eq(per_id)),
+            "item" => query.filter(item_id.eq(per_id)).filter(petc_id.eq("")),
+            "tag" => query.filter(tname.eq(per_id)).filter(petc_id.eq("")),
+            "user" => query.filter(uname.eq(per_id)),
+            _ => query, // just get some newest
         };
+        let query = match per_to {
+            "rut" | "petc" => query,
+            _ => query.filter(rut_id.eq("").or(rut_id.eq_any(listed))),
+        };
+        let query = match per.kind.trim() {
+            "" => query,
+            k => query.filter(kind.eq(k.to_owned())),
+        };
+        let query = match per.sort.trim() {
+            "score" => query.order((vote.desc(), post_at.desc())),
+            // excerpts in reading order
+            "location" => query.order((
+                page.asc(),
+                percent.asc(),
+                location.asc(),
+                post_at.asc(),
+            )),
+            _ => query.order(post_at.desc()),
+        };
+        let etc_list = query
+            .limit(PER_PAGE.into())
+            .offset((PER_PAGE * (p - 1)).into())
+            .load::<Etc>(conn)?;
 
         Ok(EtcListMsg {
             status: 200,
@@ -127,3 +238,314 @@ impl Handler<QueryEtcs> for Dba {
         })
     }
 }
+
+// handle msg from api::etc.update
+impl Handler<UpdateEtc> for Dba {
+    type Result = Result<EtcMsg, ServiceError>;
+
+    fn handle(&mut self, up_etc: UpdateEtc, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::etcs::dsl::*;
+        let conn = &self.0.get()?;
+
+        let etc_q = etcs.filter(&id.eq(&up_etc.id)).get_result::<Etc>(conn)?;
+        // check permission
+        if etc_q.uname != up_etc.uname {
+            return Err(ServiceError::Unauthorized);
+        }
+        if etc_q.deleted {
+            return Err(ServiceError::BadRequest("Deleted".into()));
+        }
+
+        let etc_update = diesel::update(&etc_q)
+            .set((
+                content_html.eq(md_to_html(&up_etc.content)),
+                content.eq(up_etc.content),
+                edited_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .get_result::<Etc>(conn)?;
+        let mentioned = save_mentions(
+            conn,
+            &etc_update.content,
+            &etc_update.uname,
+            "etc",
+            &etc_update.id,
+            &etc_update.rut_id,
+            &etc_update.item_id,
+        )?;
+        for m in mentioned.iter() {
+            notify(
+                conn,
+                m,
+                &etc_update.uname,
+                "mention",
+                "etc",
+                &etc_update.id,
+                &etc_update.content,
+            )?;
+        }
+        index_doc(conn, Doc::Etc(&etc_update));
+
+        Ok(EtcMsg {
+            status: 201,
+            message: "Updated".to_string(),
+            etc: etc_update,
+        })
+    }
+}
+
+// handle msg from api::etc.delete
+impl Handler<DelEtc> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, de: DelEtc, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::etcs::dsl::*;
+        let conn = &self.0.get()?;
+
+        let etc_q = etcs.filter(&id.eq(&de.etc_id)).get_result::<Etc>(conn)?;
+        // check permission
+        if etc_q.uname != de.uname {
+            return Err(ServiceError::Unauthorized);
+        }
+        if etc_q.deleted {
+            return Err(ServiceError::BadRequest("Deleted".into()));
+        }
+
+        conn.transaction::<_, ServiceError, _>(|| {
+            del_mentions(conn, "etc", &etc_q.id)?;
+            if etc_q.reply_count > 0 {
+                // keep as tombstone, not to break the thread
+                diesel::update(&etc_q)
+                    .set((content.eq(""), content_html.eq(""), deleted.eq(true)))
+                    .execute(conn)?;
+            } else {
+                diesel::delete(&etc_q).execute(conn)?;
+                del_etc_votes(conn, &etc_q.id)?;
+                // update reply_count - 1 in parent,
+                // and clean up the tombstone parent which no replies left
+                let mut pid = etc_q.petc_id.clone();
+                while pid != "" {
+                    let p_etc = etcs.filter(&id.eq(&pid)).load::<Etc>(conn)?.pop();
+                    match p_etc {
+                        Some(pe) => {
+                            if pe.deleted && pe.reply_count <= 1 {
+                                diesel::delete(&pe).execute(conn)?;
+                                del_etc_votes(conn, &pe.id)?;
+                                pid = pe.petc_id;
+                            } else {
+                                diesel::update(&pe)
+                                    .set(reply_count.eq(reply_count - 1))
+                                    .execute(conn)?;
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+            }
+
+            // update comment_count - 1 in ruts
+            if &etc_q.rut_id != "" {
+                use crate::schema::ruts::dsl::*;
+                diesel::update(ruts.filter(&id.eq(&etc_q.rut_id)))
+                    .set(comment_count.eq(comment_count - 1))
+                    .execute(conn)?;
+                vote_rut(conn, &etc_q.rut_id)?;
+            }
+            // update etc_count - 1 in items
+            if &etc_q.item_id != "" {
+                use crate::schema::items::dsl::*;
+                diesel::update(items.filter(&id.eq(&etc_q.item_id)))
+                    .set(etc_count.eq(etc_count - 1))
+                    .execute(conn)?;
+                vote_item(conn, &etc_q.item_id)?;
+            }
+            // untag the etc, then etc_count - 1 in tags
+            use crate::schema::tagetcs::dsl::{etc_id, tagetcs, tname as te_name};
+            let tnames = tagetcs
+                .filter(&etc_id.eq(&etc_q.id))
+                .select(te_name)
+                .load::<String>(conn)?;
+            if tnames.len() > 0 {
+                diesel::delete(tagetcs.filter(&etc_id.eq(&etc_q.id))).execute(conn)?;
+                use crate::schema::tags::dsl::{etc_count as t_etc_count, tags, tname as t_name};
+                diesel::update(tags.filter(&t_name.eq(any(&tnames))))
+                    .set(t_etc_count.eq(t_etc_count - 1))
+                    .execute(conn)?;
+                vote_tags(conn, &tnames)?;
+            }
+            Ok(())
+        })?;
+        remove_doc(&etc_q.id);
+
+        Ok(Msg {
+            status: 204,
+            message: "Deleted".to_string(),
+        })
+    }
+}
+
+// the votes on a hard-deleted etc
+fn del_etc_votes(conn: &PgConnection, eid: &str) -> Result<(), ServiceError> {
+    use crate::schema::votes::dsl::{to_id, vote_to, votes};
+    diesel::delete(votes.filter(&vote_to.eq("etc")).filter(&to_id.eq(eid))).execute(conn)?;
+    Ok(())
+}
+
+// max levels of etc tree
+const TREE_DEPTH: i32 = 8;
+
+// handle msg from api::etc.get_tree
+impl Handler<QueryEtcTree> for Dba {
+    type Result = Result<EtcTreeMsg, ServiceError>;
+
+    fn handle(&mut self, per: QueryEtcTree, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::etcs::dsl::*;
+        let conn = &self.0.get()?;
+
+        let p = per.page;
+        // eliminate no limit
+        if p < 1 {
+            return Err(ServiceError::BadRequest(
+                "400: No Requested Resource".into(),
+            ));
+        }
+        // unset as full depth
+        let depth = if per.depth < 1 {
+            TREE_DEPTH
+        } else {
+            std::cmp::min(per.depth, TREE_DEPTH)
+        };
+        let by_vote = per.sort.trim() == "vote";
+        let per_to = per.per.trim();
+        let per_id = &per.perid;
+        let viewer = &per.viewer;
+        check_per_view(conn, per_to, per_id, viewer)?;
+
+        // top level, paging
+        let etc_num: i64 = top_etcs(per_to, per_id, viewer)?
+            .count()
+            .get_result(conn)?;
+        let top_query = top_etcs(per_to, per_id, viewer)?;
+        let top_query = if by_vote {
+            top_query.order((vote.desc(), post_at.desc()))
+        } else {
+            top_query.order(post_at.desc())
+        };
+        let top_list = top_query
+            .limit(PER_PAGE.into())
+            .offset((PER_PAGE * (p - 1)).into())
+            .load::<Etc>(conn)?;
+
+        // replies, level by level
+        let mut replies: HashMap<String, Vec<Etc>> = HashMap::new();
+        let mut pids: Vec<String> = top_list
+            .iter()
+            .filter(|e| e.reply_count > 0)
+            .map(|e| e.id.clone())
+            .collect();
+        let mut level = 1;
+        while level < depth && pids.len() > 0 {
+            let reply_query = etcs.filter(&petc_id.eq(any(&pids)));
+            let reply_list = if by_vote {
+                reply_query
+                    .order((vote.desc(), post_at.asc()))
+                    .load::<Etc>(conn)?
+            } else {
+                reply_query.order(post_at.asc()).load::<Etc>(conn)?
+            };
+            pids = reply_list
+                .iter()
+                .filter(|e| e.reply_count > 0)
+                .map(|e| e.id.clone())
+                .collect();
+            for r in reply_list {
+                replies
+                    .entry(r.petc_id.clone())
+                    .or_insert_with(Vec::new)
+                    .push(r);
+            }
+            level += 1;
+        }
+
+        let etc_tree: Vec<EtcNode> = top_list
+            .into_iter()
+            .map(|e| build_node(e, &mut replies))
+            .collect();
+
+        Ok(EtcTreeMsg {
+            status: 200,
+            message: "Get".to_string(),
+            etcs: etc_tree,
+            count: etc_num as usize,
+        })
+    }
+}
+
+// etcs of a rut, or the thread on a rut, to who can view the rut only
+fn check_per_view(
+    conn: &PgConnection,
+    per_to: &str,
+    per_id: &str,
+    viewer: &str,
+) -> Result<(), ServiceError> {
+    use crate::schema::etcs::dsl::*;
+
+    let rutid = match per_to {
+        "rut" => per_id.to_owned(),
+        // a reply inherit rut of parent
+        "petc" => etcs
+            .filter(&id.eq(per_id))
+            .select(rut_id)
+            .load::<String>(conn)?
+            .pop()
+            .unwrap_or_default(),
+        _ => return Ok(()),
+    };
+    if rutid != "" {
+        viewable_rut(conn, &rutid, viewer)?;
+    }
+    Ok(())
+}
+
+// top level etcs of rut|item|tag, or replies of a petc
+fn top_etcs<'a>(
+    per_to: &str,
+    per_id: &'a str,
+    viewer: &'a str,
+) -> Result<crate::schema::etcs::BoxedQuery<'a, Pg>, ServiceError> {
+    use crate::schema::etcs::dsl::*;
+    use crate::schema::ruts::dsl::{id as rid, ruts, uname as r_uname, visibility};
+
+    // not on rut, or in public ruts, or the ruts owned by viewer
+    let listed = ruts
+        .filter(visibility.eq("public").or(r_uname.eq(viewer)))
+        .select(rid);
+    let query = etcs.into_boxed();
+    let query = match per_to {
+        "rut" => query.filter(rut_id.eq(per_id)).filter(petc_id.eq("")),
+        "item" => query
+            .filter(item_id.eq(per_id))
+            .filter(petc_id.eq(""))
+            .filter(rut_id.eq("").or(rut_id.eq_any(listed))),
+        "tag" => query
+            .filter(tname.eq(per_id))
+            .filter(petc_id.eq(""))
+            .filter(rut_id.eq("").or(rut_id.eq_any(listed))),
+        "petc" => query.filter(petc_id.eq(per_id)),
+        _ => return Err(ServiceError::BadRequest("Invalid Per".into())),
+    };
+
+    Ok(query)
+}
+
+// build etc node with its loaded replies
+fn build_node(etc: Etc, replies: &mut HashMap<String, Vec<Etc>>) -> EtcNode {
+    let children = replies.remove(&etc.id).unwrap_or_default();
+    EtcNode {
+        replies: children
+            .into_iter()
+            .map(|e| build_node(e, replies))
+            .collect(),
+        etc,
+    }
+}
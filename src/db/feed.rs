@@ -0,0 +1,180 @@
+// feed typed model and msg handler
+
+use actix::Handler;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::{self, dsl::any, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::errors::ServiceError;
+use crate::model::feed::{Feed, FeedEntry, QueryFeed};
+use crate::model::item::{Collect, Item, StarItem};
+use crate::model::rut::Rut;
+use crate::util::share::site_url;
+use crate::Dba;
+
+// entries per feed
+const FEED_LIMIT: i64 = 20;
+
+// handle msg from api::feed.get_feed
+impl Handler<QueryFeed> for Dba {
+    type Result = Result<Feed, ServiceError>;
+
+    fn handle(&mut self, qf: QueryFeed, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+        let site = site_url();
+
+        match qf.per.trim() {
+            // items of rut, per collect_at
+            "rut" => {
+                use crate::schema::collects::dsl::{collect_at, collects, rut_id};
+                use crate::schema::ruts::dsl::{id as rid, ruts};
+                let rut_q = ruts.filter(&rid.eq(&qf.perid)).get_result::<Rut>(conn)?;
+                // feed reader is anonymous
+                if !rut_q.can_view("") {
+                    return Err(ServiceError::NotFound("Not Found".into()));
+                }
+                let collect_list = collects
+                    .filter(&rut_id.eq(&rut_q.id))
+                    .order(collect_at.desc())
+                    .limit(FEED_LIMIT)
+                    .load::<Collect>(conn)?;
+                let item_list = load_items(conn, collect_list.iter().map(|c| &c.item_id))?;
+
+                let entries = collect_list
+                    .iter()
+                    .filter_map(|c| {
+                        item_list.iter().find(|i| i.id == c.item_id).map(|i| FeedEntry {
+                            id: format!("urn:uuid:{}", c.id),
+                            title: i.title.clone(),
+                            link: format!("{}/item/{}", site, i.slug),
+                            author: c.uname.clone(),
+                            summary: c.content.clone(),
+                            updated: c.collect_at,
+                        })
+                    })
+                    .collect();
+
+                Ok(Feed {
+                    id: format!("urn:uuid:{}", rut_q.id),
+                    title: rut_q.title,
+                    link: format!("{}/r/{}", site, rut_q.slug),
+                    updated: rut_q.renew_at,
+                    entries,
+                })
+            }
+            // new ruts under tag
+            "tag" => {
+                use crate::schema::ruts::dsl::{create_at, id as rid, ruts, visibility};
+                use crate::schema::tagruts::dsl::{rut_id, tagruts, tname};
+                let rut_list = ruts
+                    .filter(rid.eq_any(tagruts.filter(tname.eq(&qf.perid)).select(rut_id)))
+                    .filter(visibility.eq("public"))
+                    .order(create_at.desc())
+                    .limit(FEED_LIMIT)
+                    .load::<Rut>(conn)?;
+
+                Ok(Feed {
+                    id: format!("{}/tag/{}", site, qf.perid),
+                    title: format!("#{} on RutHub", qf.perid),
+                    link: format!("{}/tag/{}", site, qf.perid),
+                    updated: last_updated(rut_list.iter().map(|r| r.renew_at)),
+                    entries: rut_list.iter().map(|r| rut_entry(&site, r)).collect(),
+                })
+            }
+            // user's created ruts and done items
+            "user" => {
+                use crate::schema::ruts::dsl::{create_at, ruts, uname as r_uname, visibility};
+                use crate::schema::staritems::dsl::{
+                    flag, star_at, staritems, uname as s_uname,
+                };
+                let rut_list = ruts
+                    .filter(&r_uname.eq(&qf.perid))
+                    .filter(visibility.eq("public"))
+                    .order(create_at.desc())
+                    .limit(FEED_LIMIT)
+                    .load::<Rut>(conn)?;
+                let done_list = staritems
+                    .filter(&s_uname.eq(&qf.perid))
+                    .filter(&flag.eq(3))
+                    .order(star_at.desc())
+                    .limit(FEED_LIMIT)
+                    .load::<StarItem>(conn)?;
+                let item_list = load_items(conn, done_list.iter().map(|s| &s.item_id))?;
+
+                let mut entries: Vec<FeedEntry> =
+                    rut_list.iter().map(|r| rut_entry(&site, r)).collect();
+                for s in done_list.iter() {
+                    if let Some(i) = item_list.iter().find(|i| i.id == s.item_id) {
+                        entries.push(FeedEntry {
+                            id: format!("urn:uuid:{}", s.id),
+                            title: format!("Done: {}", i.title),
+                            link: format!("{}/item/{}", site, i.slug),
+                            author: s.uname.clone(),
+                            summary: s.note.clone(),
+                            updated: s.star_at,
+                        });
+                    }
+                }
+                entries.sort_by(|a, b| b.updated.cmp(&a.updated));
+                entries.truncate(FEED_LIMIT as usize);
+
+                Ok(Feed {
+                    id: format!("{}/u/{}", site, qf.perid),
+                    title: format!("{} on RutHub", qf.perid),
+                    link: format!("{}/u/{}", site, qf.perid),
+                    updated: last_updated(entries.iter().map(|e| e.updated)),
+                    entries,
+                })
+            }
+            // site-wide index, as QueryRuts::Index in mode new
+            "index" => {
+                use crate::schema::ruts::dsl::{renew_at, ruts, visibility};
+                let rut_list = ruts
+                    .filter(visibility.eq("public"))
+                    .order(renew_at.desc())
+                    .limit(FEED_LIMIT)
+                    .load::<Rut>(conn)?;
+
+                Ok(Feed {
+                    id: format!("{}/", site),
+                    title: "RutHub".to_owned(),
+                    link: format!("{}/", site),
+                    updated: last_updated(rut_list.iter().map(|r| r.renew_at)),
+                    entries: rut_list.iter().map(|r| rut_entry(&site, r)).collect(),
+                })
+            }
+            _ => Err(ServiceError::BadRequest("Invalid Per".into())),
+        }
+    }
+}
+
+fn rut_entry(site: &str, r: &Rut) -> FeedEntry {
+    FeedEntry {
+        id: format!("urn:uuid:{}", r.id),
+        title: r.title.clone(),
+        link: format!("{}/r/{}", site, r.slug),
+        author: r.uname.clone(),
+        summary: r.content.clone(),
+        updated: r.renew_at,
+    }
+}
+
+fn load_items<'a, I>(conn: &PgConnection, ids: I) -> Result<Vec<Item>, ServiceError>
+where
+    I: Iterator<Item = &'a String>,
+{
+    use crate::schema::items::dsl::{id, items};
+
+    let id_list: Vec<String> = ids.cloned().collect();
+    if id_list.len() == 0 {
+        return Ok(Vec::new());
+    }
+    let item_list = items.filter(&id.eq(any(&id_list))).load::<Item>(conn)?;
+
+    Ok(item_list)
+}
+
+// the latest, or epoch if empty, to keep stable for conditional get
+fn last_updated<I: Iterator<Item = NaiveDateTime>>(times: I) -> NaiveDateTime {
+    times.max().unwrap_or_else(|| NaiveDateTime::from_timestamp(0, 0))
+}
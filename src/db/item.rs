@@ -2,15 +2,23 @@
 
 use actix::Handler;
 use chrono::Utc;
+use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use diesel::{
     self, dsl::any, ExpressionMethods, 
     PgTextExpressionMethods, QueryDsl, RunQueryDsl
 };
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::bot::WebPage;
+use crate::db::collab::{can_edit, can_view, viewable_rut};
+use crate::db::mention::{del_mentions, save_mentions};
+use crate::db::notify::notify;
+use crate::db::search::{fuzzy_items, paging};
+use crate::db::webhook::enqueue;
 use crate::errors::ServiceError;
+use crate::hub::publish;
 use crate::model::item::{
     Collect, CollectItem, DelCollect, Item, NewItem, 
     NewStarItem, QueryCollect, QueryCollects, QueryItem, 
@@ -18,7 +26,11 @@ use crate::model::item::{
 };
 use crate::model::msg::{CollectMsg, CollectsMsg, ItemListMsg, ItemMsg, Msg, StarItemMsg};
 use crate::model::rut::Rut;
+use crate::model::search::SearchScope;
 use crate::model::PER_PAGE;
+use crate::score::{vote_item, vote_rut};
+use crate::search::{backend, index_doc, reindex_doc, Doc, DocID};
+use crate::util::md::md_to_html;
 use crate::util::share::gen_slug;
 use crate::Dba;
 
@@ -63,6 +75,7 @@ impl Handler<NewItem> for Dba {
         let item_new = diesel::insert_into(items)
             .values(&new_item)
             .get_result::<Item>(conn)?;
+        index_doc(conn, Doc::Item(&item_new));
 
         Ok(ItemMsg {
             status: 201,
@@ -104,6 +117,7 @@ impl Handler<UpdateItem> for Dba {
                 slug.eq(i_slug),
             ))
             .get_result::<Item>(conn)?;
+        index_doc(conn, Doc::Item(&item_update));
 
         Ok(ItemMsg {
             status: 201,
@@ -144,6 +158,8 @@ impl Handler<QueryItems> for Dba {
         let mut item_id_vec: Vec<String> = Vec::new();
         let mut item_list: Vec<Item> = Vec::new();
         let mut item_num = 0; // total
+        let mut highlights = HashMap::new();
+        let mut suggestion = String::new();
 
         // better do some limit
         match perid {
@@ -151,18 +167,15 @@ impl Handler<QueryItems> for Dba {
                 item_list = items.filter(&id.eq(&i)).load::<Item>(conn)?;
             }
             QueryItems::Title(t) => {
-                item_list = items
-                    .filter(&title.ilike(&t)) // ilike: %k%, %k, k%
-                    .or_filter(&uiid.ilike(&t))
-                    .limit(10)
-                    .load::<Item>(conn)?;
+                // fuzzy per title, authors, uiid, w/ did-you-mean
+                let (i_list, sug) = fuzzy_items(conn, &t)?;
+                item_list = i_list;
+                suggestion = sug;
             }
             QueryItems::Uiid(d) => {
-                item_list = items
-                    .filter(&uiid.ilike(&d))
-                    .or_filter(&title.ilike(&d))
-                    .limit(10)
-                    .load::<Item>(conn)?;
+                let (i_list, sug) = fuzzy_items(conn, &d)?;
+                item_list = i_list;
+                suggestion = sug;
             }
             QueryItems::ItemUrl(u) => {
                 // query in db or via spider
@@ -196,14 +209,24 @@ impl Handler<QueryItems> for Dba {
                     .select(item_id)
                     .load::<String>(conn)?;
             }
-            QueryItems::TagID(pid) => {
+            QueryItems::TagID(pid, s) => {
                 use crate::schema::tagitems::dsl::*;
-                item_id_vec = tagitems
-                    .filter(&tname.eq(&pid))
-                    .order(count.desc())
-                    .limit(PER_PAGE.into()) // just limit most
-                    .select(item_id)
-                    .load::<String>(conn)?;
+                let query = tagitems.filter(tname.eq(pid));
+                if s.trim() == "score" {
+                    // order per net votes
+                    use crate::schema::items::dsl::{id as iid, score};
+                    item_list = items
+                        .filter(iid.eq_any(query.select(item_id)))
+                        .order(score.desc())
+                        .limit(PER_PAGE.into()) // just limit most
+                        .load::<Item>(conn)?;
+                } else {
+                    item_id_vec = query
+                        .order(count.desc())
+                        .limit(PER_PAGE.into()) // just limit most
+                        .select(item_id)
+                        .load::<String>(conn)?;
+                }
             }
             QueryItems::UserID(pid, f, p) => {
                 use crate::schema::staritems::dsl::*;
@@ -225,46 +248,14 @@ impl Handler<QueryItems> for Dba {
                 };
             }
             QueryItems::KeyID(k, f, i, p) => {
-                // per keyword from taged, star
-                let fr = f.trim();
-                match fr {
-                    "user" => {
-                        use crate::schema::staritems::dsl::{flag, item_id, staritems, uname};
-                        let ids = staritems
-                            .filter(&uname.eq(&i))
-                            .filter(&flag.eq(3)) // just search done
-                            .select(item_id)
-                            .load::<String>(conn)?;
-                        item_list = items
-                            .filter(&title.ilike(&k))
-                            .filter(&id.eq(any(&ids)))
-                            .order(rut_count.desc())
-                            .limit(PER_PAGE.into())
-                            .load::<Item>(conn)?;
-                    }
-                    "tag" => {
-                        // hope never use, to optimaze
-                        use crate::schema::tagitems::dsl::{item_id, tagitems, tname};
-                        let ids = tagitems
-                            .filter(&tname.eq(&i))
-                            .select(item_id)
-                            .load::<String>(conn)?;
-                        item_list = items
-                            .filter(&title.ilike(&k))
-                            .filter(&id.eq(any(&ids)))
-                            .order(rut_count.desc())
-                            .limit(PER_PAGE.into())
-                            .load::<Item>(conn)?;
-                    }
-                    _ => {
-                        // just query per keyword, hope never use
-                        item_list = items
-                            .filter(&title.ilike(&k))
-                            .order(rut_count.desc())
-                            .limit(PER_PAGE.into())
-                            .load::<Item>(conn)?;
-                    }
-                }
+                // per keyword from taged, star, ranked in full-text
+                let (limit, offset) = paging(p)?;
+                let scope = SearchScope::per(f.trim(), &i);
+                let (i_list, h_map, total) =
+                    backend().search_items(conn, &k, &scope, limit, offset)?;
+                item_list = i_list;
+                highlights = h_map;
+                item_num = total;
             }
         };
 
@@ -284,6 +275,8 @@ impl Handler<QueryItems> for Dba {
             message: "Success".to_string(),
             items: item_list,
             count: item_count,
+            highlights,
+            suggestion,
         })
     }
 }
@@ -293,65 +286,124 @@ impl Handler<CollectItem> for Dba {
     type Result = Result<CollectMsg, ServiceError>;
 
     fn handle(&mut self, collect: CollectItem, _: &mut Self::Context) -> Self::Result {
-        use crate::schema::collects::dsl::*;
-        use crate::schema::items::dsl::{cover, id as itemid, items, rut_count};
-        use crate::schema::ruts::dsl::{id as rid, item_count, logo, renew_at, ruts};
         let conn = &self.0.get()?;
+        let c_msg = collect_item(conn, collect)?;
+        after_collect(conn, &c_msg.collect);
+        Ok(c_msg)
+    }
+}
 
-        // to check if have collected
-        let check_collect = collects
-            .filter(&rut_id.eq(&collect.rut_id))
-            .filter(&item_id.eq(&collect.item_id))
-            .load::<Collect>(conn)?
-            .pop();
-        if let Some(c) = check_collect {
-            return Err(ServiceError::BadRequest("400: Duplicate".into()));
-        }
-
-        // get item cover then as rut logo, and check if item exist
-        let item_q = items
-            .filter(&itemid.eq(&collect.item_id))
-            .get_result::<Item>(conn)?;
+// collect an item into rut, also on accepting a suggestion,
+// db only so can be in transaction, then after_collect once committed
+pub fn collect_item(
+    conn: &PgConnection,
+    collect: CollectItem,
+) -> Result<CollectMsg, ServiceError> {
+    use crate::schema::collects::dsl::*;
+    use crate::schema::items::dsl::{cover, id as itemid, items, rut_count};
+    use crate::schema::ruts::dsl::{id as rid, item_count, logo, renew_at, ruts};
+
+    // to check if have collected
+    let check_collect = collects
+        .filter(&rut_id.eq(&collect.rut_id))
+        .filter(&item_id.eq(&collect.item_id))
+        .load::<Collect>(conn)?
+        .pop();
+    if let Some(c) = check_collect {
+        return Err(ServiceError::BadRequest("400: Duplicate".into()));
+    }
 
-        // to gen item order, curr_item_count + 1, or pass from frontend
-        let rutID = collect.clone().rut_id;
-        let rut_q = ruts //query once for select/update
-            .filter(&rid.eq(&rutID))
-            .get_result::<Rut>(conn)?;
-        let item_num = (&rut_q).item_count;
-        // limit the item_count to 42
-        if item_num >= 42 {
-            return Err(ServiceError::BadRequest("418: Answer 42".into()));
-        }
+    // get item cover then as rut logo, and check if item exist
+    let item_q = items
+        .filter(&itemid.eq(&collect.item_id))
+        .get_result::<Item>(conn)?;
+
+    // to gen item order, curr_item_count + 1, or pass from frontend
+    let rutID = collect.clone().rut_id;
+    let rut_q = ruts //query once for select/update
+        .filter(&rid.eq(&rutID))
+        .get_result::<Rut>(conn)?;
+    // check permission, owner or editor of rut
+    if !can_edit(conn, &rut_q, &collect.uname)? {
+        return Err(ServiceError::Unauthorized);
+    }
+    let item_num = (&rut_q).item_count;
+    // limit the item_count to 42
+    if item_num >= 42 {
+        return Err(ServiceError::BadRequest("418: Answer 42".into()));
+    }
 
-        // new collect
-        let uuid_v4 = uuid::Uuid::new_v4();
-        let uid = format!("{}", uuid_v4);
-        let i_order = (item_num + 1) as i16;
-        let new_collect = Collect::new(uid, i_order, collect);
-        let collect_new = diesel::insert_into(collects)
-            .values(&new_collect)
-            .get_result::<Collect>(conn)?;
+    // new collect
+    let uuid_v4 = uuid::Uuid::new_v4();
+    let uid = format!("{}", uuid_v4);
+    let i_order = (item_num + 1) as i16;
+    let new_collect = Collect::new(uid, i_order, collect);
+    let collect_new = diesel::insert_into(collects)
+        .values(&new_collect)
+        .get_result::<Collect>(conn)?;
+    let mentioned = save_mentions(
+        conn,
+        &collect_new.content,
+        &collect_new.uname,
+        "collect",
+        &collect_new.id,
+        &collect_new.rut_id,
+        &collect_new.item_id,
+    )?;
+    notify(
+        conn,
+        &rut_q.uname,
+        &collect_new.uname,
+        "collect_rut",
+        "collect",
+        &collect_new.id,
+        &item_q.title,
+    )?;
+    for m in mentioned.iter().filter(|m| *m != &rut_q.uname) {
+        notify(
+            conn,
+            m,
+            &collect_new.uname,
+            "mention",
+            "collect",
+            &collect_new.id,
+            &collect_new.content,
+        )?;
+    }
 
-        // to update the item_count + 1 and logo and renew_at in rut
-        diesel::update(&rut_q)
-            .set((
-                item_count.eq(item_count + 1),
-                logo.eq(&item_q.cover),
-                renew_at.eq(Utc::now().naive_utc()),
-            ))
-            .execute(conn)?;
-        // to update the rut_count + 1 in item
-        diesel::update(&item_q)
-            .set(rut_count.eq(rut_count + 1))
-            .execute(conn)?;
+    // to update the item_count + 1 and logo and renew_at in rut
+    diesel::update(&rut_q)
+        .set((
+            item_count.eq(item_count + 1),
+            logo.eq(&item_q.cover),
+            renew_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(conn)?;
+    // to update the rut_count + 1 in item
+    diesel::update(&item_q)
+        .set(rut_count.eq(rut_count + 1))
+        .execute(conn)?;
+    vote_rut(conn, &rut_q.id)?;
+    vote_item(conn, &item_q.id)?;
+
+    enqueue(
+        conn,
+        "item_collected",
+        &[collect_new.uname.as_str(), rut_q.uname.as_str()],
+        &collect_new,
+    )?;
+
+    Ok(CollectMsg {
+        status: 201,
+        message: "Collected".to_string(),
+        collect: collect_new,
+    })
+}
 
-        Ok(CollectMsg {
-            status: 201,
-            message: "Collected".to_string(),
-            collect: collect_new,
-        })
-    }
+// side effects out of db: push to subscribers and search index
+pub fn after_collect(conn: &PgConnection, c: &Collect) {
+    publish(format!("rut:{}", c.rut_id), "collect", c);
+    reindex_doc(conn, DocID::Rut(&c.rut_id));
 }
 
 // handle msg from api::item.update_collect
@@ -365,13 +417,41 @@ impl Handler<UpdateCollect> for Dba {
         let collect_query = collects
             .filter(&id.eq(&up_collect.id))
             .get_result::<Collect>(conn)?;
-        if collect_query.uname != up_collect.uname {
+        // check permission, owner or editor of rut
+        use crate::schema::ruts::dsl::{id as rid, ruts};
+        let rut_q = ruts
+            .filter(&rid.eq(&collect_query.rut_id))
+            .get_result::<Rut>(conn)?;
+        if !can_edit(conn, &rut_q, &up_collect.uname)? {
             return Err(ServiceError::Unauthorized);
         }
 
         let collect_update = diesel::update(&collect_query)
-            .set(content.eq(up_collect.content))
+            .set((
+                content_html.eq(md_to_html(&up_collect.content)),
+                content.eq(up_collect.content),
+            ))
             .get_result::<Collect>(conn)?;
+        let mentioned = save_mentions(
+            conn,
+            &collect_update.content,
+            &collect_update.uname,
+            "collect",
+            &collect_update.id,
+            &collect_update.rut_id,
+            &collect_update.item_id,
+        )?;
+        for m in mentioned.iter() {
+            notify(
+                conn,
+                m,
+                &collect_update.uname,
+                "mention",
+                "collect",
+                &collect_update.id,
+                &collect_update.content,
+            )?;
+        }
 
         Ok(CollectMsg {
             status: 201,
@@ -394,23 +474,23 @@ impl Handler<DelCollect> for Dba {
             .get_result::<Collect>(conn)?;
 
         let query_c = q_collect.clone();
-
-        // check permission
-        if dc.uname != query_c.uname {
-            return Err(ServiceError::Unauthorized);
-        }
         // some var to use in re-order
         let order_del = query_c.item_order;
         let rutID = query_c.rut_id;
         let itemID = query_c.item_id;
 
+        use crate::schema::ruts::dsl::{id as rid, item_count, renew_at, ruts};
+        let rut_q = ruts.filter(&rid.eq(&rutID)).get_result::<Rut>(conn)?;
+        // check permission, owner or editor of rut
+        if !can_edit(conn, &rut_q, &dc.uname)? {
+            return Err(ServiceError::Unauthorized);
+        }
+
         // perform deletion
         diesel::delete(&q_collect).execute(conn)?;
+        del_mentions(conn, "collect", &q_collect.id)?;
 
         // to update the item_count - 1 and renew_at in rut
-        use crate::schema::ruts::dsl::{id as rid, item_count, renew_at, ruts};
-        let rut_q = ruts.filter(&rid.eq(&rutID)).get_result::<Rut>(conn)?;
-
         let item_num = rut_q.item_count as i16; // to use in re-order
 
         diesel::update(&rut_q)
@@ -424,18 +504,21 @@ impl Handler<DelCollect> for Dba {
         diesel::update(items.filter(&itemid.eq(&itemID)))
             .set(rut_count.eq(rut_count - 1))
             .execute(conn)?;
+        vote_rut(conn, &rutID)?;
+        vote_item(conn, &itemID)?;
         // to update the item order of collect IF not del last one
         if item_num > order_del {
             let lower = order_del + 1;
             let upper = item_num;
             diesel::update(
                 collects
-                    .filter(rut_id.eq(rutID))
+                    .filter(rut_id.eq(&rutID))
                     .filter(item_order.between(lower, upper)), // betw, inclusive
             )
             .set(item_order.eq(item_order - 1))
             .execute(conn)?;
         }
+        reindex_doc(conn, DocID::Rut(&rutID));
 
         Ok(Msg {
             status: 204,
@@ -452,31 +535,42 @@ impl Handler<QueryCollects> for Dba {
         use crate::schema::collects::dsl::*;
         let conn = &self.0.get()?;
 
+        use crate::schema::ruts::dsl::{id as rid, ruts, uname as r_uname, visibility};
         let mut collect_list: Vec<Collect> = Vec::new();
         match cid {
-            QueryCollects::RutID(r) => {
+            QueryCollects::RutID(r, v) => {
+                let rut_q = ruts.filter(&rid.eq(&r)).get_result::<Rut>(conn)?;
+                if !can_view(conn, &rut_q, &v)? {
+                    return Err(ServiceError::NotFound("Not Found".into()));
+                }
                 collect_list = collects.filter(&rut_id.eq(&r)).load::<Collect>(conn)?;
             }
             QueryCollects::ItemID(i, p) => {
+                // in public ruts only
+                let listed = ruts.filter(visibility.eq("public")).select(rid);
+                let query = collects.filter(&item_id.eq(&i)).filter(rut_id.eq_any(listed));
                 collect_list = if p < 1 {
                     // no limit
-                    collects.filter(&item_id.eq(&i)).load::<Collect>(conn)?
+                    query.load::<Collect>(conn)?
                 } else {
-                    collects
-                        .filter(&item_id.eq(&i))
+                    query
                         .order(collect_at.desc())
                         .limit(PER_PAGE.into())
                         .offset((PER_PAGE * (p - 1)).into())
                         .load::<Collect>(conn)?
                 };
             }
-            QueryCollects::UserID(u, p) => {
+            QueryCollects::UserID(u, v, p) => {
+                // in public ruts, or the ruts owned by viewer
+                let listed = ruts
+                    .filter(visibility.eq("public").or(r_uname.eq(&v)))
+                    .select(rid);
+                let query = collects.filter(&uname.eq(&u)).filter(rut_id.eq_any(listed));
                 collect_list = if p < 1 {
                     // no limit
-                    collects.filter(&uname.eq(&u)).load::<Collect>(conn)?
+                    query.load::<Collect>(conn)?
                 } else {
-                    collects
-                        .filter(&uname.eq(&u))
+                    query
                         .order(collect_at.desc())
                         .limit(PER_PAGE.into())
                         .offset((PER_PAGE * (p - 1)).into())
@@ -504,6 +598,7 @@ impl Handler<QueryCollect> for Dba {
         let collect_query = collects
             .filter(&id.eq(&cid.collect_id))
             .get_result::<Collect>(conn)?;
+        viewable_rut(conn, &collect_query.rut_id, &cid.viewer)?;
 
         Ok(CollectMsg {
             status: 200,
@@ -530,6 +625,7 @@ impl Handler<NewStarItem> for Dba {
 
         // flag
         let flg = istar.flag;
+        let was_done = check_star.as_ref().map_or(false, |s| s.flag == 3);
         let mut si: StarItem;
 
         if let Some(s) = check_star {
@@ -543,6 +639,8 @@ impl Handler<NewStarItem> for Dba {
                 diesel::update(items.filter(&itemid.eq(&istar.item_id)))
                     .set(done_count.eq(done_count + 1))
                     .execute(conn)?;
+                vote_item(conn, &istar.item_id)?;
+                enqueue(conn, "item_done", &[si.uname.as_str()], &si)?;
             }
         } else {
             // otherwise new star-item
@@ -559,6 +657,13 @@ impl Handler<NewStarItem> for Dba {
             si = diesel::insert_into(staritems)
                 .values(&new_star)
                 .get_result::<StarItem>(conn)?;
+            if flg == 3 {
+                enqueue(conn, "item_done", &[si.uname.as_str()], &si)?;
+            }
+        }
+        // done by, in search index
+        if flg == 3 || was_done {
+            reindex_doc(conn, DocID::Item(&si.item_id));
         }
 
         Ok(StarItemMsg {
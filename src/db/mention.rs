@@ -0,0 +1,136 @@
+// mention typed model and msg handler
+
+use actix::Handler;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{
+    self,
+    dsl::{all, any},
+    ExpressionMethods, QueryDsl, RunQueryDsl,
+};
+
+use crate::errors::ServiceError;
+use crate::model::mention::{Mention, QueryMentions};
+use crate::model::msg::MentionListMsg;
+use crate::model::{re_get_mentions, PER_PAGE};
+use crate::Dba;
+
+// handle msg from api::mention.get_mentions
+impl Handler<QueryMentions> for Dba {
+    type Result = Result<MentionListMsg, ServiceError>;
+
+    fn handle(&mut self, qm: QueryMentions, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::mentions::dsl::*;
+        let conn = &self.0.get()?;
+
+        let p = qm.page;
+        // eliminate no limit
+        if p < 1 {
+            return Err(ServiceError::BadRequest(
+                "400: No Requested Resource".into(),
+            ));
+        }
+
+        // not in rut, or in public ruts, or the ruts owned by viewer
+        use crate::schema::ruts::dsl::{id as rid, ruts, uname as r_uname, visibility};
+        let listed = ruts
+            .filter(visibility.eq("public").or(r_uname.eq(&qm.viewer)))
+            .select(rid);
+        let mention_list = mentions
+            .filter(&uname.eq(&qm.uname))
+            .filter(rut_id.eq("").or(rut_id.eq_any(listed)))
+            .order(mention_at.desc())
+            .limit(PER_PAGE.into())
+            .offset((PER_PAGE * (p - 1)).into())
+            .load::<Mention>(conn)?;
+
+        Ok(MentionListMsg {
+            status: 200,
+            message: "Get".to_string(),
+            mentions: mention_list.clone(),
+            count: mention_list.len(),
+        })
+    }
+}
+
+// parse @uname in content, save mentions of existing users,
+// replace the old if re-saved, return the newly mentioned
+pub fn save_mentions(
+    conn: &PgConnection,
+    text: &str,
+    by: &str,
+    to: &str,
+    toID: &str,
+    rutID: &str,
+    itemID: &str,
+) -> Result<Vec<String>, ServiceError> {
+    use crate::schema::mentions::dsl::*;
+
+    let names: Vec<String> = re_get_mentions(text)
+        .into_iter()
+        .filter(|n| n != by)
+        .collect();
+    // only the existing users
+    let valid_names = if names.len() > 0 {
+        use crate::schema::users::dsl::{uname as u_name, users};
+        users
+            .filter(&u_name.eq(any(&names)))
+            .select(u_name)
+            .load::<String>(conn)?
+    } else {
+        Vec::new()
+    };
+
+    let old_names = mentions
+        .filter(&mention_to.eq(to))
+        .filter(&to_id.eq(toID))
+        .select(uname)
+        .load::<String>(conn)?;
+    // del the mention removed in edit
+    diesel::delete(
+        mentions
+            .filter(&mention_to.eq(to))
+            .filter(&to_id.eq(toID))
+            .filter(&uname.ne(all(&valid_names))),
+    )
+    .execute(conn)?;
+
+    let new_names: Vec<String> = valid_names
+        .into_iter()
+        .filter(|n| !old_names.contains(n))
+        .collect();
+    let new_mentions: Vec<Mention> = new_names
+        .iter()
+        .map(|n| Mention {
+            id: format!("{}", uuid::Uuid::new_v4()),
+            uname: n.to_owned(),
+            by_uname: by.to_owned(),
+            mention_to: to.to_owned(),
+            to_id: toID.to_owned(),
+            rut_id: rutID.to_owned(),
+            item_id: itemID.to_owned(),
+            mention_at: Utc::now().naive_utc(),
+        })
+        .collect();
+    if new_mentions.len() > 0 {
+        diesel::insert_into(mentions)
+            .values(&new_mentions)
+            .execute(conn)?;
+    }
+
+    Ok(new_names)
+}
+
+// del mentions in deleted etc|collect
+pub fn del_mentions(conn: &PgConnection, to: &str, toID: &str) -> Result<(), ServiceError> {
+    use crate::schema::mentions::dsl::*;
+
+    diesel::delete(
+        mentions
+            .filter(&mention_to.eq(to))
+            .filter(&to_id.eq(toID)),
+    )
+    .execute(conn)?;
+
+    Ok(())
+}
@@ -1,6 +1,14 @@
+pub mod collab;
+pub mod digest;
 pub mod etc;
+pub mod feed;
 pub mod item;
+pub mod mention;
+pub mod notify;
+pub mod recommend;
 pub mod rut;
+pub mod search;
+pub mod suggest;
 pub mod tag;
 ///  msg handler mod
 // msg handler,
@@ -8,3 +16,114 @@ pub mod tag;
 // comm with db
 // send back he result
 pub mod user;
+pub mod vote;
+pub mod webhook;
+
+use actix::Handler;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel::{self, RunQueryDsl};
+
+use crate::errors::ServiceError;
+use crate::model::msg::Msg;
+use crate::model::{RecomputeVotes, RenderHtml};
+use crate::score::recompute_votes;
+use crate::util::md::md_to_html;
+use crate::Dba;
+
+// rows per batch in render html
+const RENDER_BATCH: i64 = 200;
+
+// handle msg from task::Scheduler, backfill the html of content|intro
+impl Handler<RenderHtml> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, _: RenderHtml, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+        let mut render_count = 0;
+
+        // per table, source column, html column
+        let to_render = [
+            ("ruts", "content", "content_html"),
+            ("collects", "content", "content_html"),
+            ("etcs", "content", "content_html"),
+            ("tags", "intro", "intro_html"),
+            ("users", "intro", "intro_html"),
+        ];
+        for (table, src, html) in to_render.iter() {
+            render_count += render_table(conn, table, src, html)?;
+        }
+
+        Ok(Msg {
+            status: 200,
+            message: format!("Rendered {}", render_count),
+        })
+    }
+}
+
+#[derive(QueryableByName)]
+struct ToRender {
+    #[sql_type = "Text"]
+    id: String,
+    #[sql_type = "Text"]
+    src: String,
+}
+
+// page by id, for the source rendered as empty, return the number of rows rendered
+fn render_table(
+    conn: &PgConnection,
+    table: &str,
+    src: &str,
+    html: &str,
+) -> Result<usize, ServiceError> {
+    let select_sql = format!(
+        "SELECT id, {src} AS src FROM {t} WHERE {html} = '' AND {src} != '' AND id > $1 \
+         ORDER BY id LIMIT $2",
+        t = table,
+        src = src,
+        html = html,
+    );
+    let update_sql = format!("UPDATE {} SET {} = $1 WHERE id = $2", table, html);
+
+    let mut count = 0;
+    let mut last_id = String::new();
+    loop {
+        let rows = diesel::sql_query(select_sql.as_str())
+            .bind::<Text, _>(&last_id)
+            .bind::<BigInt, _>(RENDER_BATCH)
+            .load::<ToRender>(conn)?;
+        for r in &rows {
+            // not empty, or to be picked up again on every start
+            let mut rendered = md_to_html(&r.src);
+            if rendered.trim() == "" {
+                rendered = "<p></p>".to_owned();
+            }
+            diesel::sql_query(update_sql.as_str())
+                .bind::<Text, _>(&rendered)
+                .bind::<Text, _>(&r.id)
+                .execute(conn)?;
+        }
+        count += rows.len();
+        match rows.last() {
+            Some(r) if rows.len() as i64 == RENDER_BATCH => last_id = r.id.clone(),
+            _ => break,
+        }
+    }
+
+    Ok(count)
+}
+
+// handle msg from task::Scheduler, re-cal vote per the weights
+impl Handler<RecomputeVotes> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, _: RecomputeVotes, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+        let num = recompute_votes(conn)?;
+
+        Ok(Msg {
+            status: 200,
+            message: format!("Recomputed {}", num),
+        })
+    }
+}
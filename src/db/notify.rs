@@ -0,0 +1,234 @@
+// notification typed model and msg handler
+
+use actix::Handler;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::errors::ServiceError;
+use crate::hub::publish;
+use crate::model::msg::{Msg, NotifyCountMsg, NotifyListMsg, NotifySettingsMsg};
+use crate::model::notify::{
+    CountNotifications, Notification, NotifySetting, QueryNotifications, QueryNotifySettings,
+    ReadNotification, UpdateNotifySetting, NOTIFY_ACTIONS,
+};
+use crate::model::PER_PAGE;
+use crate::Dba;
+
+// max chars of content excerpt in notification
+const EXCERPT_LEN: usize = 128;
+
+// handle msg from api::notify.get_list
+impl Handler<QueryNotifications> for Dba {
+    type Result = Result<NotifyListMsg, ServiceError>;
+
+    fn handle(&mut self, qn: QueryNotifications, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::notifications::dsl::*;
+        let conn = &self.0.get()?;
+
+        let p = qn.page;
+        // eliminate no limit
+        if p < 1 {
+            return Err(ServiceError::BadRequest(
+                "400: No Requested Resource".into(),
+            ));
+        }
+
+        let query = notifications.filter(&uname.eq(&qn.uname)).into_boxed();
+        let query = if qn.unread {
+            query.filter(is_read.eq(false))
+        } else {
+            query
+        };
+        let notify_list = query
+            .order(notify_at.desc())
+            .limit(PER_PAGE.into())
+            .offset((PER_PAGE * (p - 1)).into())
+            .load::<Notification>(conn)?;
+
+        Ok(NotifyListMsg {
+            status: 200,
+            message: "Get".to_string(),
+            notifications: notify_list.clone(),
+            count: notify_list.len(),
+        })
+    }
+}
+
+// handle msg from api::notify.get_count
+impl Handler<CountNotifications> for Dba {
+    type Result = Result<NotifyCountMsg, ServiceError>;
+
+    fn handle(&mut self, cn: CountNotifications, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::notifications::dsl::*;
+        let conn = &self.0.get()?;
+
+        let unread_count: i64 = notifications
+            .filter(&uname.eq(&cn.uname))
+            .filter(&is_read.eq(false))
+            .count()
+            .get_result(conn)?;
+
+        Ok(NotifyCountMsg {
+            status: 200,
+            message: "Get".to_string(),
+            count: unread_count,
+        })
+    }
+}
+
+// handle msg from api::notify.mark_read
+impl Handler<ReadNotification> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, rn: ReadNotification, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::notifications::dsl::*;
+        let conn = &self.0.get()?;
+
+        let query = notifications.filter(&uname.eq(&rn.uname));
+        if rn.nid == "all" {
+            diesel::update(query)
+                .set(is_read.eq(rn.read))
+                .execute(conn)?;
+        } else {
+            let num = diesel::update(query.filter(&id.eq(&rn.nid)))
+                .set(is_read.eq(rn.read))
+                .execute(conn)?;
+            if num == 0 {
+                return Err(ServiceError::NotFound("Not Found".into()));
+            }
+        }
+
+        Ok(Msg {
+            status: 200,
+            message: if rn.read { "Read" } else { "Unread" }.to_string(),
+        })
+    }
+}
+
+// handle msg from api::notify.get_settings
+impl Handler<QueryNotifySettings> for Dba {
+    type Result = Result<NotifySettingsMsg, ServiceError>;
+
+    fn handle(&mut self, qs: QueryNotifySettings, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+
+        Ok(NotifySettingsMsg {
+            status: 200,
+            message: "Get".to_string(),
+            settings: get_settings(conn, &qs.uname)?,
+        })
+    }
+}
+
+// handle msg from api::notify.update_setting
+impl Handler<UpdateNotifySetting> for Dba {
+    type Result = Result<NotifySettingsMsg, ServiceError>;
+
+    fn handle(&mut self, us: UpdateNotifySetting, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::notifysettings::dsl::*;
+        let conn = &self.0.get()?;
+
+        let act = us.action.trim();
+        let check_setting = notifysettings
+            .filter(&uname.eq(&us.uname))
+            .filter(&action.eq(act))
+            .load::<NotifySetting>(conn)?
+            .pop();
+        match check_setting {
+            Some(s) => {
+                diesel::update(&s)
+                    .set(enabled.eq(us.enabled))
+                    .execute(conn)?;
+            }
+            None => {
+                let new_setting = NotifySetting {
+                    id: format!("{}", uuid::Uuid::new_v4()),
+                    uname: us.uname.clone(),
+                    action: act.to_owned(),
+                    enabled: us.enabled,
+                };
+                diesel::insert_into(notifysettings)
+                    .values(&new_setting)
+                    .execute(conn)?;
+            }
+        }
+
+        Ok(NotifySettingsMsg {
+            status: 200,
+            message: "Updated".to_string(),
+            settings: get_settings(conn, &us.uname)?,
+        })
+    }
+}
+
+// preference per action, default as enabled
+fn get_settings(conn: &PgConnection, uname: &str) -> Result<Vec<NotifySetting>, ServiceError> {
+    use crate::schema::notifysettings::dsl::{notifysettings, uname as s_uname};
+
+    let saved = notifysettings
+        .filter(&s_uname.eq(uname))
+        .load::<NotifySetting>(conn)?;
+    let settings = NOTIFY_ACTIONS
+        .iter()
+        .map(|a| match saved.iter().find(|s| &s.action == a) {
+            Some(s) => s.clone(),
+            None => NotifySetting {
+                id: "".to_owned(),
+                uname: uname.to_owned(),
+                action: a.to_string(),
+                enabled: true,
+            },
+        })
+        .collect();
+
+    Ok(settings)
+}
+
+// save a notification,
+// skip if self action, or disabled in preference
+pub fn notify(
+    conn: &PgConnection,
+    to: &str,
+    by: &str,
+    act: &str,
+    to_obj: &str,
+    to_objid: &str,
+    text: &str,
+) -> Result<(), ServiceError> {
+    if to == "" || to == by {
+        return Ok(());
+    }
+
+    use crate::schema::notifysettings::dsl::{
+        action as s_action, enabled, notifysettings, uname as s_uname,
+    };
+    let disabled = notifysettings
+        .filter(&s_uname.eq(to))
+        .filter(&s_action.eq(act))
+        .filter(&enabled.eq(false))
+        .count()
+        .get_result::<i64>(conn)?;
+    if disabled > 0 {
+        return Ok(());
+    }
+
+    use crate::schema::notifications::dsl::*;
+    let new_notify = Notification {
+        id: format!("{}", uuid::Uuid::new_v4()),
+        uname: to.to_owned(),
+        actor: by.to_owned(),
+        action: act.to_owned(),
+        obj: to_obj.to_owned(),
+        objid: to_objid.to_owned(),
+        content: text.chars().take(EXCERPT_LEN).collect(),
+        is_read: false,
+        notify_at: Utc::now().naive_utc(),
+    };
+    diesel::insert_into(notifications)
+        .values(&new_notify)
+        .execute(conn)?;
+    publish(format!("user:{}", to), "notification", &new_notify);
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+// recommend msg handler: item-item similarity, personalized recommendations
+
+use actix::Handler;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel::{self, RunQueryDsl};
+
+use crate::db::search::paging;
+use crate::errors::ServiceError;
+use crate::model::msg::{Msg, RecItemsMsg};
+use crate::model::recommend::{QueryRecommendations, QuerySimilarItems, RecItem, RefreshItemSims};
+use crate::Dba;
+
+// implicit preference of user on item per staritems:
+// done > doing > todo, a low rate halves it
+const PREF_WEIGHT: &str = "
+    CASE flag WHEN 3 THEN 1.0 WHEN 2 THEN 0.6 ELSE 0.3 END
+    * CASE WHEN rate BETWEEN 1 AND 2 THEN 0.5 ELSE 1.0 END";
+// weight of a rut collecting the item, as a basket
+const COLLECT_WEIGHT: f64 = 0.8;
+// keep the top similar items per item
+const SIMS_PER_ITEM: i32 = 24;
+
+// co-occurrence of items in a same user's stars or a same public rut,
+// score = cosine of the weighted vectors: sum(wa * wb) / (|a| * |b|)
+fn item_sims_sql() -> String {
+    format!(
+        "
+    WITH pref AS (
+        SELECT uname AS who, item_id, MAX({pw})::FLOAT8 AS w
+        FROM staritems GROUP BY uname, item_id
+        UNION ALL
+        SELECT 'rut:' || c.rut_id AS who, c.item_id, {cw}::FLOAT8 AS w
+        FROM collects c JOIN ruts r ON r.id = c.rut_id AND r.visibility = 'public'
+        GROUP BY c.rut_id, c.item_id
+    ), norm AS (
+        SELECT item_id, SQRT(SUM(w * w)) AS n FROM pref GROUP BY item_id
+    ), co AS (
+        SELECT a.item_id, b.item_id AS similar_id, COUNT(*) AS n, SUM(a.w * b.w) AS dot
+        FROM pref a JOIN pref b ON a.who = b.who AND a.item_id != b.item_id
+        GROUP BY a.item_id, b.item_id
+    ), ranked AS (
+        SELECT co.item_id, co.similar_id, co.n,
+            co.dot / (na.n * nb.n) AS score,
+            ROW_NUMBER() OVER (
+                PARTITION BY co.item_id ORDER BY co.dot / (na.n * nb.n) DESC, co.n DESC
+            ) AS rk
+        FROM co
+        JOIN norm na ON na.item_id = co.item_id
+        JOIN norm nb ON nb.item_id = co.similar_id
+    )
+    INSERT INTO itemsims (id, item_id, similar_id, co_count, score, cal_at)
+    SELECT item_id || '-' || similar_id, item_id, similar_id, n::INTEGER, score, NOW()
+    FROM ranked WHERE rk <= {top}
+",
+        pw = PREF_WEIGHT,
+        cw = COLLECT_WEIGHT,
+        top = SIMS_PER_ITEM,
+    )
+}
+
+const SIMILAR_ITEMS_SQL: &str = "
+    SELECT i.*, s.score FROM itemsims s JOIN items i ON i.id = s.similar_id
+    WHERE s.item_id = $1
+    ORDER BY s.score DESC
+";
+
+// sum of similarity to the items the user starred, weighted per preference,
+// exclude the starred
+fn recommend_sql() -> String {
+    format!(
+        "
+    SELECT i.*, r.score FROM (
+        SELECT s.similar_id, SUM(s.score * p.w)::FLOAT8 AS score
+        FROM itemsims s
+        JOIN (SELECT item_id, {pw} AS w FROM staritems WHERE uname = $1) p
+            ON p.item_id = s.item_id
+        WHERE s.similar_id NOT IN (SELECT item_id FROM staritems WHERE uname = $1)
+        GROUP BY s.similar_id
+        ORDER BY score DESC LIMIT $2 OFFSET $3
+    ) r JOIN items i ON i.id = r.similar_id
+    ORDER BY r.score DESC
+",
+        pw = PREF_WEIGHT,
+    )
+}
+
+// cold start: popular items not starred yet
+const POPULAR_ITEMS_SQL: &str = "
+    SELECT i.*, 0::FLOAT8 AS score FROM items i
+    WHERE i.id NOT IN (SELECT item_id FROM staritems WHERE uname = $1)
+    ORDER BY i.rut_count DESC, i.done_count DESC LIMIT $2 OFFSET $3
+";
+
+// handle msg from task, re-cal item similarity
+impl Handler<RefreshItemSims> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, _: RefreshItemSims, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::itemsims::dsl::*;
+        let conn = &self.0.get()?;
+
+        // replace all in one transaction, readers never see an empty table
+        let num = conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(itemsims).execute(conn)?;
+            diesel::sql_query(item_sims_sql()).execute(conn)
+        })?;
+
+        Ok(Msg {
+            status: 200,
+            message: format!("Refreshed {}", num),
+        })
+    }
+}
+
+// handle msg from api::recommend.get_similar
+impl Handler<QuerySimilarItems> for Dba {
+    type Result = Result<RecItemsMsg, ServiceError>;
+
+    fn handle(&mut self, qs: QuerySimilarItems, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+
+        let item_list = diesel::sql_query(SIMILAR_ITEMS_SQL)
+            .bind::<Text, _>(&qs.item_id)
+            .load::<RecItem>(conn)?;
+
+        Ok(RecItemsMsg {
+            status: 200,
+            message: "Success".to_string(),
+            count: item_list.len(),
+            items: item_list,
+        })
+    }
+}
+
+// handle msg from api::recommend.get_recommendations
+impl Handler<QueryRecommendations> for Dba {
+    type Result = Result<RecItemsMsg, ServiceError>;
+
+    fn handle(&mut self, qr: QueryRecommendations, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+        let (limit, offset) = paging(qr.page)?;
+
+        let mut item_list = diesel::sql_query(recommend_sql())
+            .bind::<Text, _>(&qr.uname)
+            .bind::<BigInt, _>(limit)
+            .bind::<BigInt, _>(offset)
+            .load::<RecItem>(conn)?;
+        if item_list.is_empty() && offset == 0 {
+            item_list = diesel::sql_query(POPULAR_ITEMS_SQL)
+                .bind::<Text, _>(&qr.uname)
+                .bind::<BigInt, _>(limit)
+                .bind::<BigInt, _>(offset)
+                .load::<RecItem>(conn)?;
+        }
+
+        Ok(RecItemsMsg {
+            status: 200,
+            message: "Success".to_string(),
+            count: item_list.len(),
+            items: item_list,
+        })
+    }
+}
@@ -3,15 +3,29 @@
 use actix::Handler;
 use chrono::Utc;
 use diesel::prelude::*;
-use diesel::{self, dsl::any, ExpressionMethods, PgTextExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel::pg::PgConnection;
+use diesel::sql_types::{BigInt, Text};
+use diesel::{self, dsl::any, ExpressionMethods, QueryDsl, RunQueryDsl};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::db::collab::{can_edit, can_view, viewable_rut};
+use crate::db::notify::notify;
+use crate::db::search::paging;
+use crate::db::webhook::enqueue;
 use crate::errors::ServiceError;
-use crate::model::msg::{Msg, RutListMsg, RutMsg, StarStatusMsg};
+use crate::hub::publish;
+use crate::model::etc::Etc;
+use crate::model::msg::{Msg, RelatedRutsMsg, RutListMsg, RutMsg, StarStatusMsg};
 use crate::model::rut::{
-    CreateRut, QueryRut, QueryRuts, Rut, StarOrRut, StarRut, StarRutStatus, UpdateRut,
+    CreateRut, QueryRelatedRuts, QueryRut, QueryRuts, RelatedRut, Rut, StarOrRut, StarRut,
+    StarRutStatus, UpdateRut,
 };
+use crate::model::search::SearchScope;
 use crate::model::PER_PAGE;
+use crate::score::{hot_sql, top_sql, vote_rut, window_days};
+use crate::search::{backend, index_doc, Doc};
+use crate::util::md::md_to_html;
 use crate::util::share::gen_slug;
 use crate::Dba;
 
@@ -46,6 +60,8 @@ impl Handler<CreateRut> for Dba {
         let rut_new = diesel::insert_into(ruts)
             .values(&newrut)
             .get_result::<Rut>(conn)?;
+        enqueue(conn, "rut_created", &[rut_new.uname.as_str()], &rut_new)?;
+        index_doc(conn, Doc::Rut(&rut_new));
 
         Ok(RutMsg {
             status: 201,
@@ -66,6 +82,9 @@ impl Handler<QueryRut> for Dba {
         let rut_query = ruts
             .filter(&slug.eq(&rslug.rut_slug)) // slug here only
             .get_result::<Rut>(conn)?;
+        if !can_view(conn, &rut_query, &rslug.uname)? {
+            return Err(ServiceError::NotFound("Not Found".into()));
+        }
 
         Ok(RutMsg {
             status: 200,
@@ -86,19 +105,44 @@ impl Handler<QueryRuts> for Dba {
         let mut id_list: Vec<String> = Vec::new();
         let mut rut_list: Vec<Rut> = Vec::new();
         let mut rut_num = 0; // total
+        let mut highlights = HashMap::new();
 
         // build id_list per query type
         match per {
-            QueryRuts::Index(_) => {
-                rut_list = ruts
-                    .order(renew_at.desc())
-                    //.order(vote.desc())
-                    .limit(20)
-                    .load::<Rut>(conn)?;
+            QueryRuts::Index(m, w, p) => {
+                let (limit, offset) = paging(p)?;
+                let query = ruts.filter(visibility.eq("public"));
+                rut_num = query.count().get_result(conn)?;
+                rut_list = match m.as_str() {
+                    "hot" => diesel::sql_query(hot_sql())
+                        .bind::<BigInt, _>(limit)
+                        .bind::<BigInt, _>(offset)
+                        .load::<Rut>(conn)?,
+                    "top" => match window_days(&w) {
+                        Some(days) => diesel::sql_query(top_sql(days))
+                            .bind::<BigInt, _>(limit)
+                            .bind::<BigInt, _>(offset)
+                            .load::<Rut>(conn)?,
+                        None => query
+                            .order((vote.desc(), renew_at.desc()))
+                            .limit(limit)
+                            .offset(offset)
+                            .load::<Rut>(conn)?,
+                    },
+                    // new, per the latest activity
+                    _ => query
+                        .order(renew_at.desc())
+                        .limit(limit)
+                        .offset(offset)
+                        .load::<Rut>(conn)?,
+                };
             }
-            QueryRuts::UserID(u, f, p) => {
+            QueryRuts::UserID(u, f, v, p) => {
                 if &f == "create" {
-                    let query = ruts.filter(uname.eq(u));
+                    // all to the owner, otherwise public only
+                    let query = ruts
+                        .filter(uname.eq(&u))
+                        .filter(visibility.eq("public").or(uname.eq(&v)));
                     rut_num = query.clone().count().get_result(conn)?;
                     rut_list = if p < 1 {
                         // no limit, hope never use
@@ -111,8 +155,13 @@ impl Handler<QueryRuts> for Dba {
                             .load::<Rut>(conn)?
                     };
                 } else {
+                    use crate::schema::ruts::dsl::{id as rid, uname as r_uname};
                     use crate::schema::starruts::dsl::*;
-                    let query = starruts.filter(uname.eq(u));
+                    // the starred ruts viewable in list
+                    let listed = ruts
+                        .filter(visibility.eq("public").or(r_uname.eq(&v)))
+                        .select(rid);
+                    let query = starruts.filter(uname.eq(&u)).filter(rut_id.eq_any(listed));
                     rut_num = query.clone().count().get_result(conn)?;
                     id_list = if p < 1 {
                         // no limit, hope never use
@@ -132,7 +181,9 @@ impl Handler<QueryRuts> for Dba {
             }
             QueryRuts::ItemID(i, p) => {
                 use crate::schema::collects::dsl::*;
-                let query = collects.filter(item_id.eq(i));
+                use crate::schema::ruts::dsl::id as rid;
+                let listed = ruts.filter(visibility.eq("public")).select(rid);
+                let query = collects.filter(item_id.eq(i)).filter(rut_id.eq_any(listed));
                 rut_num = query.clone().count().get_result(conn)?;
                 id_list = if p < 1 {
                     // no limit, hope never use
@@ -149,76 +200,53 @@ impl Handler<QueryRuts> for Dba {
                         .load::<String>(conn)?
                 };
             }
-            QueryRuts::TagID(t, p) => {
+            QueryRuts::TagID(t, s, p) => {
+                use crate::schema::ruts::dsl::{id as rid, score};
                 use crate::schema::tagruts::dsl::*;
-                let query = tagruts.filter(tname.eq(t));
+                let listed = ruts.filter(visibility.eq("public")).select(rid);
+                let query = tagruts.filter(tname.eq(t)).filter(rut_id.eq_any(listed));
                 rut_num = query.clone().count().get_result(conn)?;
-                id_list = if p < 1 {
-                    // no limit, hope never use
-                    query
-                        .order(count.desc())
-                        .select(rut_id)
-                        .load::<String>(conn)?
-                } else {
-                    query
-                        .order(count.desc())
-                        .limit(PER_PAGE.into())
-                        .offset((PER_PAGE * (p - 1)).into())
-                        .select(rut_id)
-                        .load::<String>(conn)?
-                };
-            }
-            QueryRuts::KeyID(k, f, i, p) => {
-                // per keyword from taged, created, collected
-                let fr = f.trim();
-                match fr {
-                    "user" => {
-                        // just use this arm
-                        rut_list = ruts
-                            .filter(&uname.eq(&i))
-                            .filter(&title.ilike(&k))
-                            .order(create_at.desc())
+                if s.trim() == "score" {
+                    // order per net votes
+                    let rut_query = ruts
+                        .filter(rid.eq_any(query.select(rut_id)))
+                        .order(score.desc());
+                    rut_list = if p < 1 {
+                        // no limit, hope never use
+                        rut_query.load::<Rut>(conn)?
+                    } else {
+                        rut_query
                             .limit(PER_PAGE.into())
-                            .load::<Rut>(conn)?;
-                    }
-                    "tag" => {
-                        // hope never use, to optimaze
-                        use crate::schema::tagruts::dsl::{rut_id, tagruts, tname};
-                        let ids = tagruts
-                            .filter(&tname.eq(&i))
+                            .offset((PER_PAGE * (p - 1)).into())
+                            .load::<Rut>(conn)?
+                    };
+                } else {
+                    id_list = if p < 1 {
+                        // no limit, hope never use
+                        query
+                            .order(count.desc())
                             .select(rut_id)
-                            .load::<String>(conn)?;
-                        rut_list = ruts
-                            .filter(&title.ilike(&k))
-                            .filter(&id.eq(any(&ids)))
-                            .order(create_at.desc())
+                            .load::<String>(conn)?
+                    } else {
+                        query
+                            .order(count.desc())
                             .limit(PER_PAGE.into())
-                            .load::<Rut>(conn)?;
-                    }
-                    "item" => {
-                        // hope never use, to optimaze
-                        use crate::schema::collects::dsl::{collects, item_id, rut_id};
-                        let ids = collects
-                            .filter(&item_id.eq(&i))
+                            .offset((PER_PAGE * (p - 1)).into())
                             .select(rut_id)
-                            .load::<String>(conn)?;
-                        rut_list = ruts
-                            .filter(&title.ilike(&k))
-                            .filter(&id.eq(any(&ids)))
-                            .order(create_at.desc())
-                            .limit(PER_PAGE.into())
-                            .load::<Rut>(conn)?;
-                    }
-                    _ => {
-                        // just query per keyword, hope never use
-                        rut_list = ruts
-                            .filter(&title.ilike(&k))
-                            .order(create_at.desc())
-                            .limit(PER_PAGE.into())
-                            .load::<Rut>(conn)?;
-                    }
+                            .load::<String>(conn)?
+                    };
                 }
             }
+            QueryRuts::KeyID(k, f, i, p) => {
+                // per keyword from taged, created, collected, ranked in full-text
+                let (limit, offset) = paging(p)?;
+                let scope = SearchScope::per(f.trim(), &i);
+                let (r_list, h_map, total) =
+                    backend().search_ruts(conn, &k, &scope, limit, offset)?;
+                rut_list = r_list;
+                highlights = h_map;
+                rut_num = total;
+            }
         }
         // build rut_list
         if id_list.len() > 0 {
@@ -231,6 +259,7 @@ impl Handler<QueryRuts> for Dba {
             message: "Success".to_string(),
             ruts: rut_list,
             count: rut_num as usize,
+            highlights,
         })
     }
 }
@@ -244,6 +273,10 @@ impl Handler<UpdateRut> for Dba {
         let conn = &self.0.get()?;
 
         let old_rut = ruts.filter(&id.eq(&rut.id)).get_result::<Rut>(conn)?;
+        // check permission, owner or editor
+        if !can_edit(conn, &old_rut, &rut.uname)? {
+            return Err(ServiceError::Unauthorized);
+        }
         // to update slug if title changed
         let r_slug = if rut.title != old_rut.title {
             let r_uuid = Uuid::parse_str(&old_rut.id)?;
@@ -251,18 +284,38 @@ impl Handler<UpdateRut> for Dba {
         } else {
             old_rut.clone().slug
         };
+        // keep visibility if not set, or not by owner
+        let r_visibility = if rut.visibility.trim() == "" || rut.uname != old_rut.uname {
+            old_rut.clone().visibility
+        } else {
+            rut.clone().visibility
+        };
 
         let rut_update = diesel::update(&old_rut)
             .set((
                 title.eq(rut.title),
                 url.eq(rut.url),
+                content_html.eq(md_to_html(&rut.content)),
                 content.eq(rut.content),
                 author.eq(rut.author),
                 credential.eq(rut.credential),
                 renew_at.eq(Utc::now().naive_utc()),
                 slug.eq(r_slug),
+                visibility.eq(r_visibility),
             ))
             .get_result::<Rut>(conn)?;
+        enqueue(conn, "rut_updated", &[rut_update.uname.as_str()], &rut_update)?;
+        index_doc(conn, Doc::Rut(&rut_update));
+        // comments on the rut to be in or out of index as well
+        if rut_update.visibility != old_rut.visibility && !backend().synced() {
+            use crate::schema::etcs::dsl::{etcs, rut_id as e_rut_id};
+            let rut_etcs = etcs
+                .filter(&e_rut_id.eq(&rut_update.id))
+                .load::<Etc>(conn)?;
+            for e in rut_etcs.iter() {
+                index_doc(conn, Doc::Etc(e));
+            }
+        }
 
         Ok(RutMsg {
             status: 201,
@@ -280,9 +333,7 @@ impl Handler<StarOrRut> for Dba {
         use crate::schema::starruts::dsl::*;
         let conn = &self.0.get()?;
 
-        use crate::schema::ruts::dsl::{
-            comment_count, id as rid, item_count, ruts, star_count, vote,
-        };
+        use crate::schema::ruts::dsl::{id as rid, ruts, star_count};
         let rut_query = ruts
             .filter(&rid.eq(&rstar.rut_id))
             .get_result::<Rut>(conn)?;
@@ -290,6 +341,10 @@ impl Handler<StarOrRut> for Dba {
 
         match rstar.action {
             1 => {
+                // star private rut by who can view only
+                if !can_view(conn, &rut_query, &rstar.uname)? {
+                    return Err(ServiceError::NotFound("Not Found".into()));
+                }
                 let uid = format!("{}", uuid::Uuid::new_v4());
                 let new_star = StarRut {
                     id: uid,
@@ -301,20 +356,29 @@ impl Handler<StarOrRut> for Dba {
                 diesel::insert_into(starruts)
                     .values(&new_star)
                     .execute(conn)?;
+                notify(
+                    conn,
+                    &rut_query.uname,
+                    &rstar.uname,
+                    "star_rut",
+                    "rut",
+                    &rut_query.id,
+                    &rut_query.title,
+                )?;
                 // to update star_count + 1 in rut
                 diesel::update(ruts.filter(&rid.eq(&rstar.rut_id)))
-                    .set((
-                        star_count.eq(star_count + 1),
-                        // cal vote, to be task
-                        vote.eq(item_count * 2 + comment_count + star_count),
-                    ))
+                    .set(star_count.eq(star_count + 1))
                     .execute(conn)?;
+                vote_rut(conn, &rstar.rut_id)?;
 
-                Ok(StarStatusMsg {
+                let star_msg = StarStatusMsg {
                     status: 200,
                     message: "star".to_string(),
                     count: s_count + 1,
-                })
+                };
+                publish(format!("rut:{}", rstar.rut_id), "star", &star_msg);
+
+                Ok(star_msg)
             }
             0 => {
                 diesel::delete(
@@ -327,12 +391,16 @@ impl Handler<StarOrRut> for Dba {
                 diesel::update(ruts.filter(&rid.eq(&rstar.rut_id)))
                     .set(star_count.eq(star_count - 1))
                     .execute(conn)?;
+                vote_rut(conn, &rstar.rut_id)?;
 
-                Ok(StarStatusMsg {
+                let star_msg = StarStatusMsg {
                     status: 200,
                     message: "unstar".to_string(),
                     count: s_count - 1,
-                })
+                };
+                publish(format!("rut:{}", rstar.rut_id), "star", &star_msg);
+
+                Ok(star_msg)
             }
             _ => Ok(StarStatusMsg {
                 status: 400,
@@ -374,3 +442,96 @@ impl Handler<StarRutStatus> for Dba {
         })
     }
 }
+
+// weight of item and tag jaccard in related score
+const RELATED_BY_ITEM: f64 = 0.7;
+const RELATED_BY_TAG: f64 = 0.3;
+const RELATED_LIMIT: i64 = 12;
+// near-duplicate: item jaccard and shared items at least
+const DUP_JACCARD: f64 = 0.8;
+const DUP_MIN_ITEMS: i64 = 3;
+
+// ruts sharing items or tags with $1, jaccard = |A & B| / |A | B|, order per score|item_score
+fn related_sql(order: &str) -> String {
+    format!(
+        "
+    WITH mine AS (
+        SELECT DISTINCT item_id FROM collects WHERE rut_id = $1
+    ), my_tags AS (
+        SELECT DISTINCT tname FROM tagruts WHERE rut_id = $1
+    ), ic AS (
+        SELECT rut_id, COUNT(DISTINCT item_id) AS n FROM collects
+        WHERE item_id IN (SELECT item_id FROM mine) AND rut_id != $1
+        GROUP BY rut_id
+    ), tc AS (
+        SELECT rut_id, COUNT(DISTINCT tname) AS n FROM tagruts
+        WHERE tname IN (SELECT tname FROM my_tags) AND rut_id != $1
+        GROUP BY rut_id
+    ), cand AS (
+        SELECT c.rut_id, COALESCE(ic.n, 0) AS shared_items, COALESCE(tc.n, 0) AS shared_tags
+        FROM (SELECT rut_id FROM ic UNION SELECT rut_id FROM tc) c
+        LEFT JOIN ic ON ic.rut_id = c.rut_id
+        LEFT JOIN tc ON tc.rut_id = c.rut_id
+    ), scored AS (
+        SELECT cand.*,
+            COALESCE(shared_items::FLOAT8 / NULLIF(
+                (SELECT COUNT(*) FROM mine)
+                + (SELECT COUNT(DISTINCT item_id) FROM collects WHERE rut_id = cand.rut_id)
+                - shared_items, 0), 0) AS item_score,
+            COALESCE(shared_tags::FLOAT8 / NULLIF(
+                (SELECT COUNT(*) FROM my_tags)
+                + (SELECT COUNT(DISTINCT tname) FROM tagruts WHERE rut_id = cand.rut_id)
+                - shared_tags, 0), 0) AS tag_score
+        FROM cand
+    )
+    SELECT r.*, s.shared_items, s.shared_tags, s.item_score,
+        ({iw} * s.item_score + {tw} * s.tag_score)::FLOAT8 AS score
+    FROM scored s JOIN ruts r ON r.id = s.rut_id
+    WHERE r.visibility = 'public'
+    ORDER BY {order} DESC, r.star_count DESC LIMIT $2
+",
+        iw = RELATED_BY_ITEM,
+        tw = RELATED_BY_TAG,
+        order = order,
+    )
+}
+
+pub fn related_ruts(conn: &PgConnection, rid: &str) -> Result<Vec<RelatedRut>, ServiceError> {
+    let rut_list = diesel::sql_query(related_sql("score"))
+        .bind::<Text, _>(rid)
+        .bind::<BigInt, _>(RELATED_LIMIT)
+        .load::<RelatedRut>(conn)?;
+    Ok(rut_list)
+}
+
+// the rut whose items heavily overlap with the rut, if any
+pub fn find_dup_rut(conn: &PgConnection, rid: &str) -> Result<Option<RelatedRut>, ServiceError> {
+    let dup = diesel::sql_query(related_sql("item_score"))
+        .bind::<Text, _>(rid)
+        .bind::<BigInt, _>(1i64)
+        .load::<RelatedRut>(conn)?
+        .pop()
+        .filter(|r| r.item_score >= DUP_JACCARD && r.shared_items >= DUP_MIN_ITEMS);
+    Ok(dup)
+}
+
+// handle msg from api::rut.get_related
+impl Handler<QueryRelatedRuts> for Dba {
+    type Result = Result<RelatedRutsMsg, ServiceError>;
+
+    fn handle(&mut self, qr: QueryRelatedRuts, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+        viewable_rut(conn, &qr.rut_id, &qr.viewer)?;
+        let rut_list = related_ruts(conn, &qr.rut_id)?;
+        // flag near-duplicate on demand, not on every collect
+        let dup_of = find_dup_rut(conn, &qr.rut_id)?;
+
+        Ok(RelatedRutsMsg {
+            status: 200,
+            message: "Success".to_string(),
+            count: rut_list.len(),
+            ruts: rut_list,
+            dup_of,
+        })
+    }
+}
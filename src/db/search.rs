@@ -0,0 +1,236 @@
+// search across ruts, items, tags, users, etcs; fuzzy lookup of item
+
+use actix::Handler;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel::{self, ExpressionMethods, PgTextExpressionMethods, QueryDsl, RunQueryDsl};
+use std::collections::HashMap;
+
+use crate::errors::ServiceError;
+use crate::model::item::Item;
+use crate::model::msg::{Msg, SearchMsg};
+use crate::model::search::{
+    clean_keyword, CommitIndex, ItemSim, Search, FUZZY_LIMIT, SEARCH_GROUP, SIMILARITY,
+    WORD_SIMILARITY,
+};
+use crate::model::tag::Tag;
+use crate::model::user::{CheckUser, User};
+use crate::model::PER_PAGE;
+use crate::search::backend;
+use crate::Dba;
+
+// limit, offset per page
+pub fn paging(p: i32) -> Result<(i64, i64), ServiceError> {
+    if p < 1 {
+        return Err(ServiceError::BadRequest("400: No Requested Resource".into()));
+    }
+    Ok((PER_PAGE as i64, (PER_PAGE * (p - 1)) as i64))
+}
+
+// fuzzy lookup item per title, authors, uiid, typo-tolerant, as in item picker
+// $1: keyword, $2: pattern in ilike, $3: limit
+const FUZZY_ITEM_SQL: &str = "SELECT i.*, GREATEST( \
+       similarity(i.title, $1), word_similarity($1, i.title), \
+       similarity(i.authors, $1), similarity(i.uiid, $1), \
+       CASE WHEN i.title ILIKE $2 OR i.uiid ILIKE $2 THEN 1 ELSE 0 END \
+     )::real AS sim \
+     FROM items i \
+     WHERE i.title % $1 OR $1 <% i.title OR i.authors % $1 OR i.uiid % $1 \
+       OR i.title ILIKE $2 OR i.uiid ILIKE $2 \
+     ORDER BY sim DESC, i.rut_count DESC LIMIT $3";
+
+// return items, and the best title as did-you-mean if not a match
+pub fn fuzzy_items(conn: &PgConnection, kw: &str) -> Result<(Vec<Item>, String), ServiceError> {
+    let k = clean_keyword(kw);
+    if k.is_empty() {
+        return Ok((Vec::new(), String::new()));
+    }
+    // threshold of operator % and <%, local to the transaction,
+    // not to be left on the pooled connection
+    let sims = conn.transaction::<_, ServiceError, _>(|| {
+        diesel::sql_query(format!(
+            "SELECT set_config('pg_trgm.similarity_threshold', '{}', true), \
+             set_config('pg_trgm.word_similarity_threshold', '{}', true)",
+            SIMILARITY, WORD_SIMILARITY
+        ))
+        .execute(conn)?;
+
+        let s = diesel::sql_query(FUZZY_ITEM_SQL)
+            .bind::<Text, _>(&k)
+            .bind::<Text, _>(like_pattern(&k))
+            .bind::<BigInt, _>(FUZZY_LIMIT)
+            .load::<ItemSim>(conn)?;
+        Ok(s)
+    })?;
+
+    let lower_k = k.to_lowercase();
+    let suggestion = match sims.first() {
+        Some(best) if best.sim < 1.0 && !best.item.title.to_lowercase().contains(&lower_k) => {
+            best.item.title.clone()
+        }
+        _ => String::new(),
+    };
+    let item_list = sims.into_iter().map(|s| s.item).collect();
+
+    Ok((item_list, suggestion))
+}
+
+// pattern in ilike, to search the short text: tname, uname
+fn like_pattern(kw: &str) -> String {
+    let k = clean_keyword(kw).replace('\\', "\\\\").replace('_', "\\_");
+    format!("%{}%", k)
+}
+
+// search tags per name, intro
+pub fn search_tags(
+    conn: &PgConnection,
+    kw: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Tag>, i64), ServiceError> {
+    use crate::schema::tags::dsl::*;
+
+    let pattern = like_pattern(kw);
+    let query = tags.filter(tname.ilike(&pattern).or(intro.ilike(&pattern)));
+    let total: i64 = query.clone().count().get_result(conn)?;
+    let tag_list = query
+        .order(vote.desc())
+        .limit(limit)
+        .offset(offset)
+        .load::<Tag>(conn)?;
+
+    Ok((tag_list, total))
+}
+
+// search users per uname, nickname
+pub fn search_users(
+    conn: &PgConnection,
+    kw: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<CheckUser>, i64), ServiceError> {
+    use crate::schema::users::dsl::*;
+
+    let pattern = like_pattern(kw);
+    let query = users.filter(uname.ilike(&pattern).or(nickname.ilike(&pattern)));
+    let total: i64 = query.clone().count().get_result(conn)?;
+    let user_list = query
+        .order(join_at.asc())
+        .limit(limit)
+        .offset(offset)
+        .load::<User>(conn)?
+        .into_iter()
+        .map(|u| CheckUser {
+            email: "".to_owned(), // not expose in search
+            ..CheckUser::from(u)
+        })
+        .collect();
+
+    Ok((user_list, total))
+}
+
+// handle msg from api::search.search
+impl Handler<Search> for Dba {
+    type Result = Result<SearchMsg, ServiceError>;
+
+    fn handle(&mut self, s: Search, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+
+        // search all: top hits per type, grouped; or per type, paging
+        let (limit, offset) = if s.kind.is_empty() {
+            (SEARCH_GROUP as i64, 0)
+        } else {
+            paging(s.page)?
+        };
+        let search_for = |t: &str| s.kind.is_empty() || s.kind == t;
+        // tag, user have no such relation, just search when no filter
+        let scope = &s.scope;
+        let no_scope = scope.tag.is_empty()
+            && scope.user.is_empty()
+            && scope.item.is_empty()
+            && scope.category.is_empty();
+
+        let k = clean_keyword(&s.q);
+        let mut facets = HashMap::new();
+        let mut highlights = HashMap::new();
+
+        let hc = backend().count(conn, &k, scope)?;
+        facets.insert("rut".to_owned(), hc.rut);
+        facets.insert("item".to_owned(), hc.item);
+        facets.insert("etc".to_owned(), hc.etc);
+
+        let mut rut_list = Vec::new();
+        if search_for("rut") && hc.rut > 0 {
+            let (r_list, h_map, _) = backend().search_ruts(conn, &k, scope, limit, offset)?;
+            rut_list = r_list;
+            highlights.extend(h_map);
+        }
+        let mut item_list = Vec::new();
+        if search_for("item") && hc.item > 0 {
+            let (i_list, h_map, _) = backend().search_items(conn, &k, scope, limit, offset)?;
+            item_list = i_list;
+            highlights.extend(h_map);
+        }
+        let mut etc_list = Vec::new();
+        if search_for("etc") && hc.etc > 0 {
+            let (e_list, h_map, _) = backend().search_etcs(conn, &k, scope, limit, offset)?;
+            etc_list = e_list;
+            highlights.extend(h_map);
+        }
+        let mut tag_list = Vec::new();
+        let mut user_list = Vec::new();
+        if no_scope {
+            let (t_list, t_count) = if search_for("tag") {
+                search_tags(conn, &k, limit, offset)?
+            } else {
+                (Vec::new(), search_tags(conn, &k, 0, 0)?.1)
+            };
+            tag_list = t_list;
+            facets.insert("tag".to_owned(), t_count);
+            let (u_list, u_count) = if search_for("user") {
+                search_users(conn, &k, limit, offset)?
+            } else {
+                (Vec::new(), search_users(conn, &k, 0, 0)?.1)
+            };
+            user_list = u_list;
+            facets.insert("user".to_owned(), u_count);
+        } else {
+            facets.insert("tag".to_owned(), 0);
+            facets.insert("user".to_owned(), 0);
+        }
+
+        let count = if s.kind.is_empty() {
+            facets.values().sum::<i64>()
+        } else {
+            facets.get(&s.kind).cloned().unwrap_or(0)
+        };
+
+        Ok(SearchMsg {
+            status: 200,
+            message: "Success".to_string(),
+            ruts: rut_list,
+            items: item_list,
+            tags: tag_list,
+            users: user_list,
+            etcs: etc_list,
+            facets,
+            highlights,
+            count: count as usize,
+        })
+    }
+}
+
+// handle msg from task::Scheduler, commit the index changed since last
+impl Handler<CommitIndex> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, _: CommitIndex, _: &mut Self::Context) -> Self::Result {
+        backend().commit()?;
+
+        Ok(Msg {
+            status: 200,
+            message: "Committed".to_string(),
+        })
+    }
+}
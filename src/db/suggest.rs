@@ -0,0 +1,241 @@
+// suggest msg handler: propose an item to others' rut, accept as collect or decline
+
+use actix::Handler;
+use chrono::Utc;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::db::collab::{can_edit, can_view};
+use crate::db::item::{after_collect, collect_item};
+use crate::db::notify::notify;
+use crate::db::search::paging;
+use crate::errors::ServiceError;
+use crate::model::item::{CollectItem, Item};
+use crate::model::msg::{SuggestListMsg, SuggestMsg};
+use crate::model::rut::Rut;
+use crate::model::suggest::{AcceptSuggest, DeclineSuggest, NewSuggest, QuerySuggests, RutSuggest};
+use crate::Dba;
+
+fn get_rut(conn: &PgConnection, rutid: &str) -> Result<Rut, ServiceError> {
+    use crate::schema::ruts::dsl::*;
+    let r = ruts.filter(&id.eq(rutid)).get_result::<Rut>(conn)?;
+    Ok(r)
+}
+
+// the pending suggestion and its rut, if who can decide on it
+fn get_pending(
+    conn: &PgConnection,
+    sid: &str,
+    who: &str,
+) -> Result<(RutSuggest, Rut), ServiceError> {
+    use crate::schema::rutsuggests::dsl::*;
+    let s = rutsuggests.filter(&id.eq(sid)).get_result::<RutSuggest>(conn)?;
+    let rut_q = get_rut(conn, &s.rut_id)?;
+    if !can_edit(conn, &rut_q, who)? {
+        return Err(ServiceError::Unauthorized);
+    }
+    if s.status != "pending" {
+        return Err(ServiceError::BadRequest("400: Decided already".into()));
+    }
+    Ok((s, rut_q))
+}
+
+// handle msg from api::suggest.new
+impl Handler<NewSuggest> for Dba {
+    type Result = Result<SuggestMsg, ServiceError>;
+
+    fn handle(&mut self, ns: NewSuggest, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutsuggests::dsl::*;
+        let conn = &self.0.get()?;
+
+        let rut_q = get_rut(conn, &ns.rut_id)?;
+        if !can_view(conn, &rut_q, &ns.uname)? {
+            return Err(ServiceError::NotFound("Not Found".into()));
+        }
+        // owner and editor just collect
+        if can_edit(conn, &rut_q, &ns.uname)? {
+            return Err(ServiceError::BadRequest("400: Collect directly".into()));
+        }
+        // check if item exist
+        let item_q = {
+            use crate::schema::items::dsl::{id as itemid, items};
+            items.filter(&itemid.eq(&ns.item_id)).get_result::<Item>(conn)?
+        };
+        // check if collected already
+        {
+            use crate::schema::collects::dsl::{collects, item_id as c_item_id, rut_id as c_rut_id};
+            let c_num: i64 = collects
+                .filter(&c_rut_id.eq(&ns.rut_id))
+                .filter(&c_item_id.eq(&ns.item_id))
+                .count()
+                .get_result(conn)?;
+            if c_num > 0 {
+                return Err(ServiceError::BadRequest("400: Collected already".into()));
+            }
+        }
+        // check if pending already
+        let s_num: i64 = rutsuggests
+            .filter(&rut_id.eq(&ns.rut_id))
+            .filter(&item_id.eq(&ns.item_id))
+            .filter(&status.eq("pending"))
+            .count()
+            .get_result(conn)?;
+        if s_num > 0 {
+            return Err(ServiceError::BadRequest("400: Duplicate".into()));
+        }
+
+        let new_suggest = RutSuggest {
+            id: format!("{}", uuid::Uuid::new_v4()),
+            rut_id: ns.rut_id,
+            item_id: ns.item_id,
+            uname: ns.uname,
+            note: ns.note,
+            status: "pending".to_owned(),
+            reason: "".to_owned(),
+            decide_by: "".to_owned(),
+            collect_id: "".to_owned(),
+            suggest_at: Utc::now().naive_utc(),
+            decide_at: None,
+        };
+        let suggest_new = diesel::insert_into(rutsuggests)
+            .values(&new_suggest)
+            .get_result::<RutSuggest>(conn)?;
+        notify(
+            conn,
+            &rut_q.uname,
+            &suggest_new.uname,
+            "suggest_rut",
+            "rut",
+            &rut_q.id,
+            &item_q.title,
+        )?;
+
+        Ok(SuggestMsg {
+            status: 201,
+            message: "Suggested".to_string(),
+            suggest: suggest_new,
+        })
+    }
+}
+
+// handle msg from api::suggest.accept
+impl Handler<AcceptSuggest> for Dba {
+    type Result = Result<SuggestMsg, ServiceError>;
+
+    fn handle(&mut self, acs: AcceptSuggest, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutsuggests::dsl::*;
+        let conn = &self.0.get()?;
+
+        let (s, rut_q) = get_pending(conn, &acs.suggest_id, &acs.uname)?;
+
+        // collect per the normal path, the note as content, can be updated then
+        let (suggest_update, collect_new) = conn.transaction::<_, ServiceError, _>(|| {
+            let c_msg = collect_item(
+                conn,
+                CollectItem {
+                    rut_id: s.rut_id.clone(),
+                    item_id: s.item_id.clone(),
+                    item_order: 0,
+                    content: s.note.clone(),
+                    uname: acs.uname.clone(),
+                },
+            )?;
+            let su = diesel::update(&s)
+                .set((
+                    status.eq("accepted"),
+                    decide_by.eq(&acs.uname),
+                    collect_id.eq(&c_msg.collect.id),
+                    decide_at.eq(Some(Utc::now().naive_utc())),
+                ))
+                .get_result::<RutSuggest>(conn)?;
+            Ok((su, c_msg.collect))
+        })?;
+        after_collect(conn, &collect_new);
+        notify(
+            conn,
+            &suggest_update.uname,
+            &acs.uname,
+            "accept_suggest",
+            "collect",
+            &suggest_update.collect_id,
+            &rut_q.title,
+        )?;
+
+        Ok(SuggestMsg {
+            status: 201,
+            message: "Accepted".to_string(),
+            suggest: suggest_update,
+        })
+    }
+}
+
+// handle msg from api::suggest.decline
+impl Handler<DeclineSuggest> for Dba {
+    type Result = Result<SuggestMsg, ServiceError>;
+
+    fn handle(&mut self, ds: DeclineSuggest, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutsuggests::dsl::*;
+        let conn = &self.0.get()?;
+
+        let (s, _) = get_pending(conn, &ds.suggest_id, &ds.uname)?;
+
+        let suggest_update = diesel::update(&s)
+            .set((
+                status.eq("declined"),
+                reason.eq(ds.reason.trim()),
+                decide_by.eq(&ds.uname),
+                decide_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .get_result::<RutSuggest>(conn)?;
+        notify(
+            conn,
+            &suggest_update.uname,
+            &ds.uname,
+            "decline_suggest",
+            "rut",
+            &suggest_update.rut_id,
+            &suggest_update.reason,
+        )?;
+
+        Ok(SuggestMsg {
+            status: 201,
+            message: "Declined".to_string(),
+            suggest: suggest_update,
+        })
+    }
+}
+
+// handle msg from api::suggest.get_list
+impl Handler<QuerySuggests> for Dba {
+    type Result = Result<SuggestListMsg, ServiceError>;
+
+    fn handle(&mut self, qs: QuerySuggests, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::rutsuggests::dsl::*;
+        let conn = &self.0.get()?;
+        let (limit, offset) = paging(qs.page)?;
+
+        let rut_q = get_rut(conn, &qs.rut_id)?;
+        if !can_edit(conn, &rut_q, &qs.uname)? {
+            return Err(ServiceError::Unauthorized);
+        }
+
+        // empty status for all
+        let query = rutsuggests
+            .filter(&rut_id.eq(&qs.rut_id))
+            .filter(status.eq(&qs.status).or(qs.status.is_empty()));
+        let s_num: i64 = query.clone().count().get_result(conn)?;
+        let suggest_list = query
+            .order(suggest_at.desc())
+            .limit(limit)
+            .offset(offset)
+            .load::<RutSuggest>(conn)?;
+
+        Ok(SuggestListMsg {
+            status: 200,
+            message: "Success".to_string(),
+            suggests: suggest_list,
+            count: s_num as usize,
+        })
+    }
+}
@@ -4,16 +4,26 @@ use actix::Handler;
 use chrono::Utc;
 use diesel::prelude::*;
 use diesel::{self, dsl::any, ExpressionMethods, PgTextExpressionMethods, QueryDsl, RunQueryDsl};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::db::collab::viewable_rut;
 use crate::errors::ServiceError;
-use crate::model::msg::{Msg, StarStatusMsg, TagListMsg, TagMsg};
+use crate::model::item::Item;
+use crate::model::msg::{Msg, RelatedTagsMsg, StarStatusMsg, SuggestTagsMsg, TagListMsg, TagMsg};
 use crate::model::tag::{
-    CheckTag, QueryTags, RutTag, StarOrTag, StarTag, StarTagStatus, Tag, TagAny, TagEtc, TagItem,
-    TagRut, UpdateTag,
+    CheckTag, ModerateRutTag, QueryRelatedTags, QueryTagVotes, QueryTags, RefreshRelatedTags,
+    RelatedTag, RutTag, StarOrTag, StarTag, StarTagStatus, SuggestTags, SuggestedTag, Tag, TagAny,
+    TagEtc, TagItem, TagRut, TagVote, UpdateTag,
 };
+use crate::score::vote_tag;
+use crate::search::{reindex_doc, DocID};
+use crate::util::md::md_to_html;
+use crate::util::share::slugify;
 use crate::Dba;
 
+sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
 // handle msg from api::tag.new_tag and get_tag
 impl Handler<CheckTag> for Dba {
     type Result = Result<TagMsg, ServiceError>;
@@ -58,7 +68,8 @@ impl Handler<QueryTags> for Dba {
         let mut tag_list: Vec<String> = Vec::new();
 
         match per {
-            QueryTags::RutID(r) => {
+            QueryTags::RutID(r, viewer) => {
+                viewable_rut(conn, &r, &viewer)?;
                 use crate::schema::tagruts::dsl::*;
                 tag_list = tagruts
                     .filter(&rut_id.eq(&r))
@@ -123,6 +134,7 @@ impl Handler<UpdateTag> for Dba {
 
         let tag_update = diesel::update(tags.filter(&tname.eq(&tg.tname)))
             .set((
+                intro_html.eq(md_to_html(&tg.intro)),
                 intro.eq(tg.intro),
                 logo.eq(tg.logo),
                 pname.eq(p_name.clone()),
@@ -147,80 +159,23 @@ impl Handler<UpdateTag> for Dba {
     }
 }
 
-// handle msg from api::tag.tag_rut :  to be optimized
+// handle msg from api::tag.tag_rut
 impl Handler<RutTag> for Dba {
     type Result = Result<Msg, ServiceError>;
 
     fn handle(&mut self, rutg: RutTag, _: &mut Self::Context) -> Self::Result {
-        use crate::schema::tagruts::dsl::*;
         let conn = &self.0.get()?;
 
         let action = rutg.action;
         let rutID = rutg.rut_id;
-
-        if action == 1 {
-            // tag
-            for rtg in rutg.tnames {
-                // to check if tagged with a same tag
-                let tr = tagruts
-                    .filter(&tname.eq(&rtg))
-                    .filter(&rut_id.eq(&rutID))
-                    .load::<TagRut>(conn)?
-                    .pop();
-                match tr {
-                    // if tagged, update count + 1 in tagruts
-                    Some(tgr) => {
-                        diesel::update(&tgr)
-                            .set(count.eq(count + 1))
-                            .execute(conn)?;
-                    }
-                    // else new tag-rut
-                    None => {
-                        let new_tag_rut = TagRut {
-                            id: (rtg.clone() + "-" + &rutID),
-                            tname: rtg.clone(),
-                            rut_id: rutID.clone(),
-                            count: 1,
-                        };
-                        //  to check if tname in tags? otherwise, new_tag
-                        use crate::schema::tags::dsl::*;
-                        let tag_check = tags.filter(&tname.eq(&rtg)).load::<Tag>(conn)?.pop();
-                        match tag_check {
-                            // if existing, tag then rut_count + 1 in tags
-                            Some(t) => {
-                                diesel::insert_into(tagruts)
-                                    .values(&new_tag_rut)
-                                    .execute(conn)?;
-                                // then update tags.rut_count
-                                diesel::update(&t)
-                                    .set((
-                                        rut_count.eq(rut_count + 1),
-                                        vote.eq((rut_count + item_count)* 2  + etc_count + star_count), // cal vote, to be task
-                                    ))
-                                    .execute(conn)?;
-                            }
-                            // if no existing tname, new_tag
-                            None => {
-                                let newtag = Tag {
-                                    rut_count: 1,
-                                    vote: 2,
-                                    ..Tag::new(rtg)
-                                };
-                                // new_tag
-                                diesel::insert_into(tags).values(&newtag).execute(conn)?;
-                                // then tag_rut
-                                diesel::insert_into(tagruts)
-                                    .values(&new_tag_rut)
-                                    .execute(conn)?;
-                            }
-                        }
-                    }
-                }
-            }
-        } else {
-            // untag
-            for rtg in rutg.tnames {
-                diesel::delete(tagruts.filter(&tname.eq(&rtg))).execute(conn)?;
+        let uname = rutg.uname;
+        viewable_rut(conn, &rutID, &uname)?;
+
+        for rtg in rutg.tnames {
+            if action == 1 {
+                tag_rut(conn, &rtg, &rutID, &uname)?;
+            } else {
+                untag_rut(conn, &rtg, &rutID, &uname)?;
             }
         }
 
@@ -237,7 +192,7 @@ impl Handler<StarOrTag> for Dba {
 
     fn handle(&mut self, tstar: StarOrTag, _: &mut Self::Context) -> Self::Result {
         use crate::schema::startags::dsl::*;
-        use crate::schema::tags::dsl::{rut_count, star_count, tags, tname as t_name, vote};
+        use crate::schema::tags::dsl::{star_count, tags, tname as t_name};
         let conn = &self.0.get()?;
 
         let tag_query = tags
@@ -274,11 +229,9 @@ impl Handler<StarOrTag> for Dba {
                     .execute(conn)?;
                 // to update star_count + 1 in tag
                 diesel::update(&tag_query)
-                    .set((
-                        star_count.eq(star_count + 1),
-                        vote.eq(rut_count * 2 + star_count), // cal vote, to be task
-                    ))
+                    .set(star_count.eq(star_count + 1))
                     .execute(conn)?;
+                vote_tag(conn, &tstar.tname)?;
 
                 Ok(StarStatusMsg {
                     status: 200,
@@ -299,6 +252,7 @@ impl Handler<StarOrTag> for Dba {
                 diesel::update(&tag_query)
                     .set(star_count.eq(star_count - 1))
                     .execute(conn)?;
+                vote_tag(conn, &tstar.tname)?;
 
                 Ok(StarStatusMsg {
                     status: 200,
@@ -358,131 +312,25 @@ impl Handler<TagAny> for Dba {
         let tag_to = tg.tag_to.trim();
         let action = tg.action;
         let toID = tg.to_id;
+        let uname = tg.uname;
 
         match tag_to {
             "rut" => {
-                use crate::schema::tagruts::dsl::*;
+                viewable_rut(conn, &toID, &uname)?;
                 for rtg in tgnames {
                     if action == 1 {
-                        // tag
-                        // to check if tagged with a same tag
-                        let tr = tagruts
-                            .filter(&tname.eq(&rtg))
-                            .filter(&rut_id.eq(&toID))
-                            .load::<TagRut>(conn)?
-                            .pop();
-                        match tr {
-                            // if tagged, update count + 1 in tagruts
-                            Some(tgr) => {
-                                diesel::update(&tgr)
-                                    .set(count.eq(count + 1))
-                                    .execute(conn)?;
-                            }
-                            // else new tag-rut
-                            None => {
-                                let new_tag_rut = TagRut {
-                                    id: (rtg.clone() + "-" + &toID),
-                                    tname: rtg.clone(),
-                                    rut_id: toID.clone(),
-                                    count: 1,
-                                };
-                                diesel::insert_into(tagruts)
-                                    .values(&new_tag_rut)
-                                    .execute(conn)?;
-                                // check tnames if existing
-                                use crate::schema::tags::dsl::{
-                                    rut_count, tags, tname as t_name, item_count, etc_count, star_count, vote
-                                };
-                                let tag_check =
-                                    tags.filter(&t_name.eq(&rtg)).load::<Tag>(conn)?.pop();
-                                match tag_check {
-                                    Some(t) => {
-                                        // then update tags.rut_count
-                                        diesel::update(&t)
-                                            .set((
-                                                rut_count.eq(rut_count + 1),
-                                                vote.eq((rut_count + item_count)* 2  + etc_count + star_count), // cal vote, to be task
-                                            ))
-                                            .execute(conn)?;
-                                    }
-                                    None => {
-                                        let newtag = Tag {
-                                            rut_count: 1,
-                                            vote: 2,
-                                            ..Tag::new(rtg)
-                                        };
-                                        // new_tag
-                                        diesel::insert_into(tags).values(&newtag).execute(conn)?;
-                                    }
-                                }
-                            }
-                        }
+                        tag_rut(conn, &rtg, &toID, &uname)?;
                     } else {
-                        // untag
-                        diesel::delete(tagruts.filter(&tname.eq(&rtg))).execute(conn)?;
+                        untag_rut(conn, &rtg, &toID, &uname)?;
                     }
                 }
             }
             "item" => {
-                use crate::schema::tagitems::dsl::*;
                 for itg in tgnames {
                     if action == 1 {
-                        // tag
-                        // to check if tagged with a same tag
-                        let ti = tagitems
-                            .filter(&tname.eq(&itg))
-                            .filter(&item_id.eq(&toID))
-                            .load::<TagItem>(conn)?
-                            .pop();
-                        match ti {
-                            // if tagged, update count + 1
-                            Some(tgi) => {
-                                diesel::update(&tgi)
-                                    .set(count.eq(count + 1))
-                                    .execute(conn)?;
-                            },
-                            // else new tag-rut
-                            None => {
-                                let new_tag_item = TagItem {
-                                    id: itg.clone() + "-" + &toID,
-                                    tname: itg.clone(),
-                                    item_id: toID.clone(),
-                                    count: 1,
-                                };
-                                diesel::insert_into(tagitems)
-                                    .values(&new_tag_item)
-                                    .execute(conn)?;
-                                // check tnames if existing
-                                use crate::schema::tags::dsl::{
-                                    item_count, tags, tname as t_name, rut_count, etc_count, star_count, vote
-                                };
-                                let tag_check =
-                                    tags.filter(&t_name.eq(&itg)).load::<Tag>(conn)?.pop();
-                                match tag_check {
-                                    Some(t) => {
-                                        // then update tags.rut_count
-                                        diesel::update(&t)
-                                            .set((
-                                                item_count.eq(item_count + 1),
-                                                vote.eq((rut_count + item_count)* 2  + etc_count + star_count), // cal vote, to be task
-                                            ))
-                                            .execute(conn)?;
-                                    },
-                                    None => {
-                                        let newtag = Tag {
-                                            item_count: 1,
-                                            vote: 2,
-                                            ..Tag::new(itg)
-                                        };
-                                        // new_tag
-                                        diesel::insert_into(tags).values(&newtag).execute(conn)?;
-                                    },
-                                }
-                            }
-                        }
+                        tag_item(conn, &itg, &toID, &uname)?;
                     } else {
-                        // untag
-                        diesel::delete(tagitems.filter(&tname.eq(&itg))).execute(conn)?;
+                        untag_item(conn, &itg, &toID, &uname)?;
                     }
                 }
             }
@@ -505,32 +353,28 @@ impl Handler<TagAny> for Dba {
                             .values(&new_tag_etc)
                             .execute(conn)?;
                         // check tnames if existing
-                        use crate::schema::tags::dsl::{
-                            tags, tname as t_name, etc_count, rut_count, item_count, star_count, vote
-                        };
+                        use crate::schema::tags::dsl::{tags, tname as t_name, etc_count};
                         let tag_check = tags.filter(&t_name.eq(&etg)).load::<Tag>(conn)?.pop();
                         match tag_check {
                             Some(t) => {
                                 // then update tags.rut_count
                                 diesel::update(&t)
-                                    .set((
-                                        etc_count.eq(etc_count + 1),
-                                        vote.eq((rut_count + item_count)* 2  + etc_count + star_count), // cal vote, to be task
-                                    ))
+                                    .set(etc_count.eq(etc_count + 1))
                                     .execute(conn)?;
                             },
                             None => {
                                 let newtag = Tag {
                                     etc_count: 1,
-                                    vote: 2,
-                                    ..Tag::new(etg)
+                                    ..Tag::new(etg.clone())
                                 };
                                 // new_tag
                                 diesel::insert_into(tags).values(&newtag).execute(conn)?;
                             },
                         }
+                        vote_tag(conn, &etg)?;
                     }
                 }
+                reindex_doc(conn, DocID::Etc(&toID));
             }
             _ => (),
         }
@@ -541,3 +385,605 @@ impl Handler<TagAny> for Dba {
         })
     }
 }
+
+// handle msg from api::tag.moderate_rut_tag, rut owner lock|unlock|remove a tag
+impl Handler<ModerateRutTag> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, md: ModerateRutTag, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::tagruts::dsl::*;
+        let conn = &self.0.get()?;
+
+        // check permission
+        use crate::schema::ruts::dsl::{id as rid, ruts, uname as r_uname};
+        let owner = ruts
+            .filter(&rid.eq(&md.rut_id))
+            .select(r_uname)
+            .get_result::<String>(conn)?;
+        if owner != md.uname {
+            return Err(ServiceError::Unauthorized);
+        }
+
+        let tgr = tagruts
+            .filter(&tname.eq(&md.tname))
+            .filter(&rut_id.eq(&md.rut_id))
+            .get_result::<TagRut>(conn)?;
+
+        match md.action.trim() {
+            "lock" => {
+                diesel::update(&tgr).set(locked.eq(true)).execute(conn)?;
+            }
+            "unlock" => {
+                diesel::update(&tgr).set(locked.eq(false)).execute(conn)?;
+            }
+            "remove" => {
+                diesel::delete(&tgr).execute(conn)?;
+                // votes go with the removed tag
+                use crate::schema::tagvotes::dsl::{tagvotes, tname as v_tname, to_id, vote_to};
+                diesel::delete(
+                    tagvotes
+                        .filter(&v_tname.eq(&md.tname))
+                        .filter(&vote_to.eq("rut"))
+                        .filter(&to_id.eq(&md.rut_id)),
+                )
+                .execute(conn)?;
+                dec_tag_count(conn, &md.tname, "rut")?;
+                reindex_doc(conn, DocID::Rut(&md.rut_id));
+            }
+            _ => return Err(ServiceError::BadRequest("Invalid Action".into())),
+        }
+
+        Ok(Msg {
+            status: 201,
+            message: "Done".to_string(),
+        })
+    }
+}
+
+// handle msg from api::tag.tag_vote_status
+impl Handler<QueryTagVotes> for Dba {
+    type Result = Result<TagListMsg, ServiceError>;
+
+    fn handle(&mut self, qv: QueryTagVotes, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::tagvotes::dsl::*;
+        let conn = &self.0.get()?;
+
+        let tag_list = tagvotes
+            .filter(&uname.eq(&qv.uname))
+            .filter(&vote_to.eq(&qv.vote_to))
+            .filter(&to_id.eq(&qv.to_id))
+            .select(tname)
+            .load::<String>(conn)?;
+
+        Ok(TagListMsg {
+            status: 200,
+            message: "Success".to_string(),
+            tags: tag_list.clone(),
+            count: tag_list.len(),
+        })
+    }
+}
+
+// a user tag a rut, one vote per user per tag, return false if voted already
+fn tag_rut(conn: &PgConnection, tg: &str, rutID: &str, uname: &str) -> Result<bool, ServiceError> {
+    use crate::schema::tagruts::dsl::*;
+
+    let changed = conn.transaction::<_, ServiceError, _>(|| {
+        if !add_tag_vote(conn, tg, "rut", rutID, uname)? {
+            return Ok(false);
+        }
+
+        // to check if tagged with a same tag
+        let tr = tagruts
+            .filter(&tname.eq(tg))
+            .filter(&rut_id.eq(rutID))
+            .load::<TagRut>(conn)?
+            .pop();
+        match tr {
+            // if tagged, update count + 1 in tagruts
+            Some(tgr) => {
+                diesel::update(&tgr)
+                    .set(count.eq(count + 1))
+                    .execute(conn)?;
+            }
+            // else new tag-rut
+            None => {
+                let new_tag_rut = TagRut {
+                    id: tg.to_owned() + "-" + rutID,
+                    tname: tg.to_owned(),
+                    rut_id: rutID.to_owned(),
+                    count: 1,
+                    locked: false,
+                };
+                diesel::insert_into(tagruts)
+                    .values(&new_tag_rut)
+                    .execute(conn)?;
+                inc_tag_count(conn, tg, "rut")?;
+            }
+        }
+
+        Ok(true)
+    })?;
+    if changed {
+        reindex_doc(conn, DocID::Rut(rutID));
+    }
+
+    Ok(changed)
+}
+
+// a user retract the tag vote on a rut, return false if not voted
+fn untag_rut(conn: &PgConnection, tg: &str, rutID: &str, uname: &str) -> Result<bool, ServiceError> {
+    use crate::schema::tagruts::dsl::*;
+
+    let changed = conn.transaction::<_, ServiceError, _>(|| {
+        let voted = del_tag_vote(conn, tg, "rut", rutID, uname)?;
+        // tagged before votes recorded, untag by anyone as before
+        if !voted && has_tag_votes(conn, tg, "rut", rutID)? {
+            return Ok(false);
+        }
+
+        let tr = tagruts
+            .filter(&tname.eq(tg))
+            .filter(&rut_id.eq(rutID))
+            .load::<TagRut>(conn)?
+            .pop();
+        if let Some(tgr) = tr {
+            // the last vote gone, untag, unless locked by rut owner
+            if (tgr.count <= 1 || !voted) && !tgr.locked {
+                diesel::delete(&tgr).execute(conn)?;
+                dec_tag_count(conn, tg, "rut")?;
+            } else if voted && tgr.count > 0 {
+                diesel::update(&tgr)
+                    .set(count.eq(count - 1))
+                    .execute(conn)?;
+            }
+        }
+
+        Ok(true)
+    })?;
+    if changed {
+        reindex_doc(conn, DocID::Rut(rutID));
+    }
+
+    Ok(changed)
+}
+
+// a user tag an item, one vote per user per tag, return false if voted already
+fn tag_item(conn: &PgConnection, tg: &str, itemID: &str, uname: &str) -> Result<bool, ServiceError> {
+    use crate::schema::tagitems::dsl::*;
+
+    let changed = conn.transaction::<_, ServiceError, _>(|| {
+        if !add_tag_vote(conn, tg, "item", itemID, uname)? {
+            return Ok(false);
+        }
+
+        // to check if tagged with a same tag
+        let ti = tagitems
+            .filter(&tname.eq(tg))
+            .filter(&item_id.eq(itemID))
+            .load::<TagItem>(conn)?
+            .pop();
+        match ti {
+            // if tagged, update count + 1
+            Some(tgi) => {
+                diesel::update(&tgi)
+                    .set(count.eq(count + 1))
+                    .execute(conn)?;
+            }
+            // else new tag-item
+            None => {
+                let new_tag_item = TagItem {
+                    id: tg.to_owned() + "-" + itemID,
+                    tname: tg.to_owned(),
+                    item_id: itemID.to_owned(),
+                    count: 1,
+                };
+                diesel::insert_into(tagitems)
+                    .values(&new_tag_item)
+                    .execute(conn)?;
+                inc_tag_count(conn, tg, "item")?;
+            }
+        }
+
+        Ok(true)
+    })?;
+    if changed {
+        reindex_doc(conn, DocID::Item(itemID));
+    }
+
+    Ok(changed)
+}
+
+// a user retract the tag vote on an item, return false if not voted
+fn untag_item(conn: &PgConnection, tg: &str, itemID: &str, uname: &str) -> Result<bool, ServiceError> {
+    use crate::schema::tagitems::dsl::*;
+
+    let changed = conn.transaction::<_, ServiceError, _>(|| {
+        let voted = del_tag_vote(conn, tg, "item", itemID, uname)?;
+        // tagged before votes recorded, untag by anyone as before
+        if !voted && has_tag_votes(conn, tg, "item", itemID)? {
+            return Ok(false);
+        }
+
+        let ti = tagitems
+            .filter(&tname.eq(tg))
+            .filter(&item_id.eq(itemID))
+            .load::<TagItem>(conn)?
+            .pop();
+        if let Some(tgi) = ti {
+            if tgi.count <= 1 || !voted {
+                diesel::delete(&tgi).execute(conn)?;
+                dec_tag_count(conn, tg, "item")?;
+            } else {
+                diesel::update(&tgi)
+                    .set(count.eq(count - 1))
+                    .execute(conn)?;
+            }
+        }
+
+        Ok(true)
+    })?;
+    if changed {
+        reindex_doc(conn, DocID::Item(itemID));
+    }
+
+    Ok(changed)
+}
+
+// record the vote of a user, return false if voted already
+fn add_tag_vote(
+    conn: &PgConnection,
+    tg: &str,
+    to: &str,
+    toID: &str,
+    user: &str,
+) -> Result<bool, ServiceError> {
+    use crate::schema::tagvotes::dsl::*;
+
+    let voted: i64 = tagvotes
+        .filter(&uname.eq(user))
+        .filter(&tname.eq(tg))
+        .filter(&vote_to.eq(to))
+        .filter(&to_id.eq(toID))
+        .count()
+        .get_result(conn)?;
+    if voted > 0 {
+        return Ok(false);
+    }
+
+    let new_vote = TagVote {
+        id: format!("{}", uuid::Uuid::new_v4()),
+        uname: user.to_owned(),
+        tname: tg.to_owned(),
+        vote_to: to.to_owned(),
+        to_id: toID.to_owned(),
+        vote_at: Utc::now().naive_utc(),
+    };
+    diesel::insert_into(tagvotes)
+        .values(&new_vote)
+        .execute(conn)?;
+
+    Ok(true)
+}
+
+// retract the vote of a user, return false if not voted
+fn del_tag_vote(
+    conn: &PgConnection,
+    tg: &str,
+    to: &str,
+    toID: &str,
+    user: &str,
+) -> Result<bool, ServiceError> {
+    use crate::schema::tagvotes::dsl::*;
+
+    let num = diesel::delete(
+        tagvotes
+            .filter(&uname.eq(user))
+            .filter(&tname.eq(tg))
+            .filter(&vote_to.eq(to))
+            .filter(&to_id.eq(toID)),
+    )
+    .execute(conn)?;
+
+    Ok(num > 0)
+}
+
+// if any vote on the tag of a rut|item
+fn has_tag_votes(
+    conn: &PgConnection,
+    tg: &str,
+    to: &str,
+    toID: &str,
+) -> Result<bool, ServiceError> {
+    use crate::schema::tagvotes::dsl::*;
+
+    let num: i64 = tagvotes
+        .filter(&tname.eq(tg))
+        .filter(&vote_to.eq(to))
+        .filter(&to_id.eq(toID))
+        .count()
+        .get_result(conn)?;
+
+    Ok(num > 0)
+}
+
+// update rut_count|item_count + 1 in tags, new tag if not existing
+fn inc_tag_count(conn: &PgConnection, tg: &str, tag_to: &str) -> Result<(), ServiceError> {
+    use crate::schema::tags::dsl::*;
+
+    let tag_check = tags.filter(&tname.eq(tg)).load::<Tag>(conn)?.pop();
+    match tag_check {
+        Some(t) => {
+            if tag_to == "rut" {
+                diesel::update(&t)
+                    .set(rut_count.eq(rut_count + 1))
+                    .execute(conn)?;
+            } else {
+                diesel::update(&t)
+                    .set(item_count.eq(item_count + 1))
+                    .execute(conn)?;
+            }
+        }
+        None => {
+            let newtag = if tag_to == "rut" {
+                Tag {
+                    rut_count: 1,
+                    ..Tag::new(tg.to_owned())
+                }
+            } else {
+                Tag {
+                    item_count: 1,
+                    ..Tag::new(tg.to_owned())
+                }
+            };
+            // new_tag
+            diesel::insert_into(tags).values(&newtag).execute(conn)?;
+        }
+    }
+    vote_tag(conn, tg)?;
+
+    Ok(())
+}
+
+// update rut_count|item_count - 1 in tags
+fn dec_tag_count(conn: &PgConnection, tg: &str, tag_to: &str) -> Result<(), ServiceError> {
+    use crate::schema::tags::dsl::*;
+
+    let tag_q = tags.filter(&tname.eq(tg));
+    if tag_to == "rut" {
+        diesel::update(tag_q)
+            .set(rut_count.eq(rut_count - 1))
+            .execute(conn)?;
+    } else {
+        diesel::update(tag_q)
+            .set(item_count.eq(item_count - 1))
+            .execute(conn)?;
+    }
+    vote_tag(conn, tg)?;
+
+    Ok(())
+}
+
+// co-occurrence of tags on a same rut or item, normalized per popularity:
+// score = co / sqrt(pop_a * pop_b), keep the top 16 per tag;
+// id prefixed w/ the length of tname, as tname may contain '-'
+const RELATED_TAGS_SQL: &str = "
+    WITH pop AS (
+        SELECT tname, COUNT(*) AS n FROM (
+            SELECT tname FROM tagruts UNION ALL SELECT tname FROM tagitems
+        ) t GROUP BY tname
+    ), co AS (
+        SELECT tname, related, SUM(n) AS n FROM (
+            SELECT a.tname, b.tname AS related, COUNT(*) AS n
+            FROM tagruts a JOIN tagruts b ON a.rut_id = b.rut_id AND a.tname != b.tname
+            GROUP BY a.tname, b.tname
+            UNION ALL
+            SELECT a.tname, b.tname AS related, COUNT(*) AS n
+            FROM tagitems a JOIN tagitems b ON a.item_id = b.item_id AND a.tname != b.tname
+            GROUP BY a.tname, b.tname
+        ) c GROUP BY tname, related
+    ), ranked AS (
+        SELECT co.tname, co.related, co.n,
+            co.n::FLOAT8 / SQRT((pa.n * pb.n)::FLOAT8) AS score,
+            ROW_NUMBER() OVER (
+                PARTITION BY co.tname ORDER BY co.n::FLOAT8 / SQRT((pa.n * pb.n)::FLOAT8) DESC
+            ) AS rk
+        FROM co
+        JOIN pop pa ON pa.tname = co.tname
+        JOIN pop pb ON pb.tname = co.related
+    )
+    INSERT INTO relatedtags (id, tname, related, co_count, score, cal_at)
+    SELECT LENGTH(tname) || ':' || tname || '-' || related,
+        tname, related, n::INTEGER, score, NOW()
+    FROM ranked WHERE rk <= 16
+";
+
+// handle msg from task, re-cal related tags
+impl Handler<RefreshRelatedTags> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, _: RefreshRelatedTags, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::relatedtags::dsl::*;
+        let conn = &self.0.get()?;
+
+        // replace all in one transaction, readers never see an empty table
+        let num = conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(relatedtags).execute(conn)?;
+            diesel::sql_query(RELATED_TAGS_SQL).execute(conn)
+        })?;
+
+        Ok(Msg {
+            status: 200,
+            message: format!("Refreshed {}", num),
+        })
+    }
+}
+
+// handle msg from api::tag.get_related
+impl Handler<QueryRelatedTags> for Dba {
+    type Result = Result<RelatedTagsMsg, ServiceError>;
+
+    fn handle(&mut self, rt: QueryRelatedTags, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::relatedtags::dsl::*;
+        let conn = &self.0.get()?;
+
+        let tag_list = relatedtags
+            .filter(&tname.eq(&rt.tname))
+            .order(score.desc())
+            .load::<RelatedTag>(conn)?;
+
+        Ok(RelatedTagsMsg {
+            status: 200,
+            message: "Success".to_string(),
+            count: tag_list.len(),
+            tags: tag_list,
+        })
+    }
+}
+
+// weight of each source in suggesting tags
+const SUGGEST_BY_TITLE: f64 = 0.6;
+const SUGGEST_BY_CATEGORY: f64 = 0.5;
+const SUGGEST_BY_DETAIL: f64 = 0.3;
+const SUGGEST_BY_RUT: f64 = 0.8; // tags of ruts the item collected into
+const SUGGEST_BY_ITEM: f64 = 0.9; // tags of items in the rut
+
+// handle msg from api::tag.suggest_item_tags and suggest_rut_tags
+impl Handler<SuggestTags> for Dba {
+    type Result = Result<SuggestTagsMsg, ServiceError>;
+
+    fn handle(&mut self, per: SuggestTags, _: &mut Self::Context) -> Self::Result {
+        let conn = &self.0.get()?;
+
+        // tname -> confidence
+        let mut cands: HashMap<String, f64> = HashMap::new();
+        let tagged: Vec<String>;
+
+        match per {
+            SuggestTags::ItemID(i) => {
+                use crate::schema::items::dsl::{id as itemid, items};
+                let item_q = items.filter(&itemid.eq(&i)).get_result::<Item>(conn)?;
+
+                // per text: title, category, detail
+                let texts = vec![
+                    (&item_q.title, SUGGEST_BY_TITLE),
+                    (&item_q.category, SUGGEST_BY_CATEGORY),
+                    (&item_q.detail, SUGGEST_BY_DETAIL),
+                ];
+                for (text, weight) in texts {
+                    for t in match_tags(conn, text)? {
+                        add_evidence(&mut cands, &t, weight);
+                    }
+                }
+
+                // per tags of ruts which collect the item
+                use crate::schema::collects::dsl::{collects, item_id, rut_id as c_rut_id};
+                let rut_ids = collects
+                    .filter(&item_id.eq(&i))
+                    .select(c_rut_id)
+                    .load::<String>(conn)?;
+                use crate::schema::tagruts::dsl::{rut_id, tagruts, tname};
+                let rut_tags = tagruts
+                    .filter(&rut_id.eq(any(&rut_ids)))
+                    .select(tname)
+                    .load::<String>(conn)?;
+                for (t, share) in share_of(rut_tags, rut_ids.len()) {
+                    add_evidence(&mut cands, &t, SUGGEST_BY_RUT * share);
+                }
+
+                use crate::schema::tagitems::dsl::{
+                    item_id as t_item_id, tagitems, tname as t_name,
+                };
+                tagged = tagitems
+                    .filter(&t_item_id.eq(&i))
+                    .select(t_name)
+                    .load::<String>(conn)?;
+            }
+            SuggestTags::RutID(r) => {
+                // per tags of items in the rut
+                use crate::schema::collects::dsl::{collects, item_id as c_item_id, rut_id};
+                let item_ids = collects
+                    .filter(&rut_id.eq(&r))
+                    .select(c_item_id)
+                    .load::<String>(conn)?;
+                use crate::schema::tagitems::dsl::{item_id, tagitems, tname};
+                let item_tags = tagitems
+                    .filter(&item_id.eq(any(&item_ids)))
+                    .select(tname)
+                    .load::<String>(conn)?;
+                for (t, share) in share_of(item_tags, item_ids.len()) {
+                    add_evidence(&mut cands, &t, SUGGEST_BY_ITEM * share);
+                }
+
+                use crate::schema::tagruts::dsl::{rut_id as t_rut_id, tagruts, tname as t_name};
+                tagged = tagruts
+                    .filter(&t_rut_id.eq(&r))
+                    .select(t_name)
+                    .load::<String>(conn)?;
+            }
+        }
+
+        // no need to suggest the tagged
+        let mut tag_list: Vec<SuggestedTag> = cands
+            .into_iter()
+            .filter(|(t, _)| !tagged.contains(t))
+            .map(|(t, c)| SuggestedTag {
+                tname: t,
+                confidence: (c * 100.0).round() / 100.0,
+            })
+            .collect();
+        tag_list.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+        tag_list.truncate(10);
+
+        Ok(SuggestTagsMsg {
+            status: 200,
+            message: "Success".to_string(),
+            count: tag_list.len(),
+            tags: tag_list,
+        })
+    }
+}
+
+// combine the evidences of a tag as noisy-or, keep confidence in 0..1
+fn add_evidence(cands: &mut HashMap<String, f64>, tag: &str, weight: f64) {
+    let c = cands.entry(tag.to_owned()).or_insert(0.0);
+    *c = 1.0 - (1.0 - *c) * (1.0 - weight.min(1.0).max(0.0));
+}
+
+// the share of targets(rut|item) taged with a same tag, tags: one per tagged target
+fn share_of(tags: Vec<String>, total: usize) -> Vec<(String, f64)> {
+    if total == 0 {
+        return Vec::new();
+    }
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for t in tags {
+        *counts.entry(t).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(t, n)| (t, n as f64 / total as f64))
+        .collect()
+}
+
+// get existing tags which name occurs in the text, as word or 2-words phrase
+fn match_tags(conn: &PgConnection, text: &str) -> Result<Vec<String>, ServiceError> {
+    use crate::schema::tags::dsl::{tags, tname};
+
+    let words: Vec<String> = slugify(text)
+        .split('-')
+        .filter(|w| w.len() >= 2)
+        .map(|w| w.to_owned())
+        .collect();
+    let mut phrases: Vec<String> = words.windows(2).map(|w| w.join("-")).collect();
+    phrases.extend(words);
+    if phrases.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tag_list = tags
+        .filter(lower(tname).eq(any(&phrases)))
+        .select(tname)
+        .load::<String>(conn)?;
+
+    Ok(tag_list)
+}
@@ -2,12 +2,17 @@
 
 use actix::Handler;
 use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::Utc;
 use diesel::prelude::*;
 use uuid::Uuid;
 
+use crate::db::notify::notify;
 use crate::errors::ServiceError;
 use crate::model::msg::{AuthMsg, Msg};
-use crate::model::user::{AuthUser, ChangePsw, CheckUser, QueryUser, RegUser, UpdateUser, User};
+use crate::model::user::{
+    AuthUser, ChangePsw, CheckUser, Follow, FollowUser, QueryUser, RegUser, UpdateUser, User,
+};
+use crate::util::md::md_to_html;
 use crate::Dba;
 
 pub fn hash_password(plain: &str) -> Result<String, ServiceError> {
@@ -111,7 +116,7 @@ impl Handler<UpdateUser> for Dba {
         let conn = &self.0.get()?;
 
         let update_user = diesel::update(users.filter(&uname.eq(&user.uname)))
-            .set(&user)
+            .set((&user, intro_html.eq(md_to_html(&user.intro))))
             .get_result::<User>(conn)?;
 
         Ok(update_user.into())
@@ -158,3 +163,70 @@ impl Handler<ChangePsw> for Dba {
         }
     }
 }
+
+// handle msg from api::auth.follow_user
+impl Handler<FollowUser> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, fo: FollowUser, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::follows::dsl::*;
+        let conn = &self.0.get()?;
+
+        if fo.uname == fo.fname {
+            return Err(ServiceError::BadRequest("Cannot Follow Self".into()));
+        }
+        // check if user existing
+        {
+            use crate::schema::users::dsl::{uname as u_name, users};
+            users
+                .filter(&u_name.eq(&fo.fname))
+                .select(u_name)
+                .get_result::<String>(conn)?;
+        }
+
+        let check_follow = follows
+            .filter(&uname.eq(&fo.uname))
+            .filter(&fname.eq(&fo.fname))
+            .load::<Follow>(conn)?
+            .pop();
+
+        match (fo.action, check_follow) {
+            (1, None) => {
+                let new_follow = Follow {
+                    id: format!("{}", uuid::Uuid::new_v4()),
+                    uname: fo.uname.clone(),
+                    fname: fo.fname.clone(),
+                    fo_at: Utc::now().naive_utc(),
+                    note: "".to_owned(),
+                };
+                diesel::insert_into(follows)
+                    .values(&new_follow)
+                    .execute(conn)?;
+                notify(conn, &fo.fname, &fo.uname, "follow", "user", &fo.uname, "")?;
+
+                Ok(Msg {
+                    status: 200,
+                    message: "follow".to_string(),
+                })
+            }
+            (1, Some(_)) => Ok(Msg {
+                status: 200,
+                message: "follow".to_string(),
+            }),
+            (0, _) => {
+                diesel::delete(
+                    follows
+                        .filter(&uname.eq(&fo.uname))
+                        .filter(&fname.eq(&fo.fname)),
+                )
+                .execute(conn)?;
+
+                Ok(Msg {
+                    status: 200,
+                    message: "unfollow".to_string(),
+                })
+            }
+            _ => Err(ServiceError::BadRequest("Invalid Action".into())),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// vote typed model and msg handler
+
+use actix::Handler;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::db::collab::viewable_rut;
+use crate::errors::ServiceError;
+use crate::model::msg::VoteMsg;
+use crate::model::vote::{Vote, VoteFor, VoteStatus};
+use crate::Dba;
+
+// handle msg from api::vote.vote
+impl Handler<VoteFor> for Dba {
+    type Result = Result<VoteMsg, ServiceError>;
+
+    fn handle(&mut self, vf: VoteFor, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::votes::dsl::*;
+        let conn = &self.0.get()?;
+
+        let to = vf.vote_to.trim();
+        // check if target existing and viewable
+        let mut s_count = get_score(conn, to, &vf.to_id, &vf.uname)?;
+
+        let check_vote = votes
+            .filter(&uname.eq(&vf.uname))
+            .filter(&vote_to.eq(to))
+            .filter(&to_id.eq(&vf.to_id))
+            .load::<Vote>(conn)?
+            .pop();
+        let old_updown = check_vote.as_ref().map(|v| v.updown).unwrap_or(0);
+
+        match (check_vote, vf.updown) {
+            (Some(v), 0) => {
+                // retract
+                diesel::delete(&v).execute(conn)?;
+            }
+            (Some(v), ud) => {
+                // change up <-> down
+                diesel::update(&v)
+                    .set((updown.eq(ud), vote_at.eq(Utc::now().naive_utc())))
+                    .execute(conn)?;
+            }
+            (None, 0) => (),
+            (None, ud) => {
+                let new_vote = Vote {
+                    id: format!("{}", uuid::Uuid::new_v4()),
+                    uname: vf.uname.clone(),
+                    vote_to: to.to_owned(),
+                    to_id: vf.to_id.clone(),
+                    updown: ud,
+                    vote_at: Utc::now().naive_utc(),
+                };
+                diesel::insert_into(votes).values(&new_vote).execute(conn)?;
+            }
+        }
+
+        // update net score of target
+        let delta = (vf.updown - old_updown) as i32;
+        if delta != 0 {
+            match to {
+                "etc" => {
+                    use crate::schema::etcs::dsl::{etcs, id as eid, vote};
+                    diesel::update(etcs.filter(&eid.eq(&vf.to_id)))
+                        .set(vote.eq(vote + delta))
+                        .execute(conn)?;
+                }
+                "rut" => {
+                    use crate::schema::ruts::dsl::{id as rid, ruts, score};
+                    diesel::update(ruts.filter(&rid.eq(&vf.to_id)))
+                        .set(score.eq(score + delta))
+                        .execute(conn)?;
+                }
+                _ => {
+                    use crate::schema::items::dsl::{id as iid, items, score};
+                    diesel::update(items.filter(&iid.eq(&vf.to_id)))
+                        .set(score.eq(score + delta))
+                        .execute(conn)?;
+                }
+            }
+            s_count += delta;
+        }
+
+        Ok(VoteMsg {
+            status: 200,
+            message: "Voted".to_string(),
+            updown: vf.updown,
+            score: s_count,
+        })
+    }
+}
+
+// handle msg from api::vote.vote_status
+impl Handler<VoteStatus> for Dba {
+    type Result = Result<VoteMsg, ServiceError>;
+
+    fn handle(&mut self, vs: VoteStatus, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::votes::dsl::*;
+        let conn = &self.0.get()?;
+
+        let to = vs.vote_to.trim();
+        let s_count = get_score(conn, to, &vs.to_id, &vs.uname)?;
+
+        let check_vote = votes
+            .filter(&uname.eq(&vs.uname))
+            .filter(&vote_to.eq(to))
+            .filter(&to_id.eq(&vs.to_id))
+            .select(updown)
+            .load::<i16>(conn)?
+            .pop();
+
+        Ok(VoteMsg {
+            status: 200,
+            message: "Success".to_string(),
+            updown: check_vote.unwrap_or(0),
+            score: s_count,
+        })
+    }
+}
+
+// get the net score of etc|rut|item, NotFound if in a rut the viewer cannot view
+fn get_score(
+    conn: &PgConnection,
+    to: &str,
+    toID: &str,
+    viewer: &str,
+) -> Result<i32, ServiceError> {
+    let s_count = match to {
+        "etc" => {
+            use crate::schema::etcs::dsl::{etcs, id, rut_id, vote};
+            let (e_rut, e_vote) = etcs
+                .filter(&id.eq(toID))
+                .select((rut_id, vote))
+                .get_result::<(String, i32)>(conn)?;
+            if e_rut.trim() != "" {
+                viewable_rut(conn, &e_rut, viewer)?;
+            }
+            e_vote
+        }
+        "rut" => viewable_rut(conn, toID, viewer)?.score,
+        "item" => {
+            use crate::schema::items::dsl::{id, items, score};
+            items
+                .filter(&id.eq(toID))
+                .select(score)
+                .get_result::<i32>(conn)?
+        }
+        _ => return Err(ServiceError::BadRequest("Invalid Input".into())),
+    };
+
+    Ok(s_count)
+}
@@ -0,0 +1,211 @@
+// webhook typed model and msg handler
+
+use actix::Handler;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{self, dsl::any, ExpressionMethods, PgArrayExpressionMethods, QueryDsl, RunQueryDsl};
+use serde::Serialize;
+
+use crate::errors::ServiceError;
+use crate::model::msg::{DeliveryListMsg, Msg, WebhookListMsg, WebhookMsg};
+use crate::model::webhook::{
+    DelWebhook, Delivery, NewWebhook, QueryDeliveries, QueryWebhooks, Webhook,
+};
+use crate::model::PER_PAGE;
+use crate::util::share::to_blob;
+use crate::Dba;
+
+// handle msg from api::webhook.new
+impl Handler<NewWebhook> for Dba {
+    type Result = Result<WebhookMsg, ServiceError>;
+
+    fn handle(&mut self, nw: NewWebhook, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::webhooks::dsl::*;
+        let conn = &self.0.get()?;
+
+        let mut evs: Vec<String> = nw.events.iter().map(|e| e.trim().to_owned()).collect();
+        evs.sort();
+        evs.dedup();
+
+        let new_webhook = Webhook {
+            id: format!("{}", uuid::Uuid::new_v4()),
+            uname: nw.uname,
+            url: nw.url.trim().to_owned(),
+            secret: to_blob(&uuid::Uuid::new_v4()) + &to_blob(&uuid::Uuid::new_v4()),
+            events: evs,
+            active: true,
+            create_at: Utc::now().naive_utc(),
+        };
+        let webhook_new = diesel::insert_into(webhooks)
+            .values(&new_webhook)
+            .get_result::<Webhook>(conn)?;
+
+        Ok(WebhookMsg {
+            status: 201,
+            message: "Created".to_string(),
+            webhook: webhook_new,
+        })
+    }
+}
+
+// handle msg from api::webhook.get_list
+impl Handler<QueryWebhooks> for Dba {
+    type Result = Result<WebhookListMsg, ServiceError>;
+
+    fn handle(&mut self, qw: QueryWebhooks, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::webhooks::dsl::*;
+        let conn = &self.0.get()?;
+
+        let webhook_list = webhooks
+            .filter(&uname.eq(&qw.uname))
+            .order(create_at.desc())
+            .load::<Webhook>(conn)?;
+
+        Ok(WebhookListMsg {
+            status: 200,
+            message: "Get".to_string(),
+            webhooks: webhook_list.clone(),
+            count: webhook_list.len(),
+        })
+    }
+}
+
+// handle msg from api::webhook.delete
+impl Handler<DelWebhook> for Dba {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, dw: DelWebhook, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::webhooks::dsl::*;
+        let conn = &self.0.get()?;
+
+        let webhook_q = webhooks
+            .filter(&id.eq(&dw.webhook_id))
+            .get_result::<Webhook>(conn)?;
+        // check permission
+        if webhook_q.uname != dw.uname {
+            return Err(ServiceError::Unauthorized);
+        }
+
+        diesel::delete(&webhook_q).execute(conn)?;
+        use crate::schema::deliveries::dsl::{deliveries, webhook_id};
+        diesel::delete(deliveries.filter(&webhook_id.eq(&webhook_q.id))).execute(conn)?;
+
+        Ok(Msg {
+            status: 204,
+            message: "Deleted".to_string(),
+        })
+    }
+}
+
+// handle msg from api::webhook.get_deliveries
+impl Handler<QueryDeliveries> for Dba {
+    type Result = Result<DeliveryListMsg, ServiceError>;
+
+    fn handle(&mut self, qd: QueryDeliveries, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::deliveries::dsl::*;
+        let conn = &self.0.get()?;
+
+        let p = qd.page;
+        // eliminate no limit
+        if p < 1 {
+            return Err(ServiceError::BadRequest(
+                "400: No Requested Resource".into(),
+            ));
+        }
+        // check permission
+        get_webhook(conn, &qd.webhook_id, &qd.uname)?;
+
+        let delivery_list = deliveries
+            .filter(&webhook_id.eq(&qd.webhook_id))
+            .order(create_at.desc())
+            .limit(PER_PAGE.into())
+            .offset((PER_PAGE * (p - 1)).into())
+            .load::<Delivery>(conn)?;
+
+        Ok(DeliveryListMsg {
+            status: 200,
+            message: "Get".to_string(),
+            deliveries: delivery_list.clone(),
+            count: delivery_list.len(),
+        })
+    }
+}
+
+// get webhook of the owner
+pub fn get_webhook(conn: &PgConnection, wid: &str, owner: &str) -> Result<Webhook, ServiceError> {
+    use crate::schema::webhooks::dsl::*;
+
+    let webhook_q = webhooks.filter(&id.eq(wid)).get_result::<Webhook>(conn)?;
+    if webhook_q.uname != owner {
+        return Err(ServiceError::Unauthorized);
+    }
+
+    Ok(webhook_q)
+}
+
+// new delivery of event, in json: {"event", "data", "at"}
+pub fn new_delivery<T: Serialize>(
+    wid: &str,
+    ev: &str,
+    data: &T,
+) -> Result<Delivery, ServiceError> {
+    let now = Utc::now().naive_utc();
+    let body = serde_json::json!({
+        "event": ev,
+        "data": data,
+        "at": now,
+    });
+
+    Ok(Delivery {
+        id: format!("{}", uuid::Uuid::new_v4()),
+        webhook_id: wid.to_owned(),
+        event: ev.to_owned(),
+        payload: serde_json::to_string(&body)
+            .map_err(|_| ServiceError::InternalServerError("payload".into()))?,
+        status: "pending".to_owned(),
+        attempts: 0,
+        response_code: None,
+        response_body: "".to_owned(),
+        create_at: now,
+        next_at: now,
+        deliver_at: None,
+    })
+}
+
+// put event into outbox of the webhooks which owner subscribe,
+// delivered by task::deliver
+pub fn enqueue<T: Serialize>(
+    conn: &PgConnection,
+    ev: &str,
+    owners: &[&str],
+    data: &T,
+) -> Result<(), ServiceError> {
+    use crate::schema::webhooks::dsl::*;
+
+    let mut owner_list: Vec<String> = owners
+        .iter()
+        .filter(|o| **o != "")
+        .map(|o| o.to_string())
+        .collect();
+    owner_list.sort();
+    owner_list.dedup();
+    let webhook_list = webhooks
+        .filter(&uname.eq(any(&owner_list)))
+        .filter(&active.eq(true))
+        .filter(&events.contains(vec![ev.to_owned()]))
+        .load::<Webhook>(conn)?;
+    if webhook_list.len() == 0 {
+        return Ok(());
+    }
+
+    let mut new_deliveries: Vec<Delivery> = Vec::new();
+    for w in webhook_list.iter() {
+        new_deliveries.push(new_delivery(&w.id, ev, data)?);
+    }
+    use crate::schema::deliveries::dsl::deliveries;
+    diesel::insert_into(deliveries)
+        .values(&new_deliveries)
+        .execute(conn)?;
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// hub mod
+
+// broadcast actor for real-time updates,
+// ws session subscribe topic: rut:{id}, tag:{tname}, user:{uname},
+// msg handler publish event to topic, then push to the subscribed sessions
+
+pub mod session;
+
+use actix::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+// as msg pushed to session, json text
+#[derive(Message)]
+pub struct Push(pub String);
+
+// as msg in new session connect, get back id
+#[derive(Message)]
+#[rtype(usize)]
+pub struct Connect {
+    pub addr: Recipient<Push>,
+}
+
+// as msg in session disconnect
+#[derive(Message)]
+pub struct Disconnect {
+    pub id: usize,
+}
+
+// as msg in session subscribe a topic
+#[derive(Message)]
+pub struct Subscribe {
+    pub id: usize,
+    pub topic: String,
+}
+
+// as msg in session unsubscribe a topic
+#[derive(Message)]
+pub struct Unsubscribe {
+    pub id: usize,
+    pub topic: String,
+}
+
+// as msg to publish event to topic
+#[derive(Message)]
+pub struct Publish {
+    pub topic: String,
+    pub event: String,
+    pub data: serde_json::Value,
+}
+
+#[derive(Default)]
+pub struct Hub {
+    sessions: HashMap<usize, Recipient<Push>>,
+    topics: HashMap<String, HashSet<usize>>,
+    next_id: usize,
+}
+
+impl Actor for Hub {
+    type Context = Context<Self>;
+}
+
+impl Supervised for Hub {}
+
+impl SystemService for Hub {}
+
+impl Handler<Connect> for Hub {
+    type Result = usize;
+
+    fn handle(&mut self, msg: Connect, _: &mut Context<Self>) -> Self::Result {
+        self.next_id = self.next_id.wrapping_add(1);
+        self.sessions.insert(self.next_id, msg.addr);
+        self.next_id
+    }
+}
+
+impl Handler<Disconnect> for Hub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Disconnect, _: &mut Context<Self>) {
+        self.sessions.remove(&msg.id);
+        for ids in self.topics.values_mut() {
+            ids.remove(&msg.id);
+        }
+        self.topics.retain(|_, ids| !ids.is_empty());
+    }
+}
+
+impl Handler<Subscribe> for Hub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Subscribe, _: &mut Context<Self>) {
+        self.topics
+            .entry(msg.topic)
+            .or_insert_with(HashSet::new)
+            .insert(msg.id);
+    }
+}
+
+impl Handler<Unsubscribe> for Hub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Unsubscribe, _: &mut Context<Self>) {
+        if let Some(ids) = self.topics.get_mut(&msg.topic) {
+            ids.remove(&msg.id);
+            if ids.is_empty() {
+                self.topics.remove(&msg.topic);
+            }
+        }
+    }
+}
+
+impl Handler<Publish> for Hub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Publish, _: &mut Context<Self>) {
+        if let Some(ids) = self.topics.get(&msg.topic) {
+            let text = serde_json::json!({
+                "topic": msg.topic,
+                "event": msg.event,
+                "data": msg.data,
+            })
+            .to_string();
+            for id in ids {
+                if let Some(addr) = self.sessions.get(id) {
+                    let _ = addr.do_send(Push(text.clone()));
+                }
+            }
+        }
+    }
+}
+
+// publish event to topic, called in msg handler,
+// no wait, ignore if failed
+pub fn publish<T: Serialize>(topic: String, event: &str, data: &T) {
+    match serde_json::to_value(data) {
+        Ok(data) => Hub::from_registry().do_send(Publish {
+            topic,
+            event: event.to_owned(),
+            data,
+        }),
+        Err(e) => warn!("publish {}: {}", topic, e),
+    }
+}
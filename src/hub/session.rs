@@ -0,0 +1,159 @@
+// ws session, an actor per connection
+
+use actix::prelude::*;
+use actix_web_actors::ws;
+use std::time::{Duration, Instant};
+
+use crate::hub::{Connect, Disconnect, Hub, Push, Subscribe, Unsubscribe};
+use crate::model::collab::CheckView;
+use crate::DbAddr;
+
+// interval of heartbeat ping
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+// timeout w/o pong from client
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub struct WsSession {
+    pub id: usize,
+    pub uname: String, // from jwt, to check user topic
+    pub hb: Instant,
+    pub db: DbAddr, // to check rut topic
+}
+
+impl WsSession {
+    pub fn new(uname: String, db: DbAddr) -> Self {
+        WsSession {
+            id: 0,
+            uname,
+            hb: Instant::now(),
+            db,
+        }
+    }
+
+    // ping client per interval, stop if timeout
+    fn heartbeat(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
+                ctx.stop();
+                return;
+            }
+            ctx.ping("");
+        });
+    }
+
+    // topic: rut:{id}, tag:{tname}, or user:{self uname}
+    fn check_topic(&self, topic: &str) -> bool {
+        let mut parts = topic.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some("rut"), Some(id)) | (Some("tag"), Some(id)) => id.trim() != "",
+            (Some("user"), Some(u)) => u == self.uname,
+            _ => false,
+        }
+    }
+
+    // private rut to who can view only, check per db before subscribe
+    fn subscribe(&self, topic: String, ctx: &mut ws::WebsocketContext<Self>) {
+        let id = self.id;
+        if !topic.starts_with("rut:") {
+            Hub::from_registry().do_send(Subscribe { id, topic });
+            return;
+        }
+        let rut_id = topic["rut:".len()..].to_owned();
+        let uname = self.uname.clone();
+        let check = self
+            .db
+            .send(CheckView { rut_id, uname })
+            .into_actor(self)
+            .then(move |res, _, ctx| {
+                match res {
+                    Ok(Ok(true)) => Hub::from_registry().do_send(Subscribe { id, topic }),
+                    _ => ctx.text(r#"{"error":"Invalid Topic"}"#),
+                }
+                fut::ok(())
+            });
+        ctx.spawn(check);
+    }
+}
+
+// client msg in text: {"action": "subscribe|unsubscribe", "topic": "rut:id"}
+#[derive(Deserialize, Debug)]
+struct ClientMsg {
+    action: String,
+    topic: String,
+}
+
+impl Actor for WsSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.heartbeat(ctx);
+
+        let addr = ctx.address();
+        Hub::from_registry()
+            .send(Connect {
+                addr: addr.recipient(),
+            })
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(id) => act.id = id,
+                    _ => ctx.stop(),
+                }
+                fut::ok(())
+            })
+            .wait(ctx);
+    }
+
+    fn stopping(&mut self, _: &mut Self::Context) -> Running {
+        Hub::from_registry().do_send(Disconnect { id: self.id });
+        Running::Stop
+    }
+}
+
+// pushed from hub
+impl Handler<Push> for WsSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: Push, ctx: &mut Self::Context) {
+        ctx.text(msg.0);
+    }
+}
+
+impl StreamHandler<ws::Message, ws::ProtocolError> for WsSession {
+    fn handle(&mut self, msg: ws::Message, ctx: &mut Self::Context) {
+        match msg {
+            ws::Message::Ping(msg) => {
+                self.hb = Instant::now();
+                ctx.pong(&msg);
+            }
+            ws::Message::Pong(_) => {
+                self.hb = Instant::now();
+            }
+            ws::Message::Text(text) => {
+                let cmsg = match serde_json::from_str::<ClientMsg>(&text) {
+                    Ok(m) => m,
+                    Err(_) => {
+                        ctx.text(r#"{"error":"Invalid Message"}"#);
+                        return;
+                    }
+                };
+                let topic = cmsg.topic.trim().to_owned();
+                if !self.check_topic(&topic) {
+                    ctx.text(r#"{"error":"Invalid Topic"}"#);
+                    return;
+                }
+                match cmsg.action.trim() {
+                    "subscribe" => self.subscribe(topic, ctx),
+                    "unsubscribe" => {
+                        Hub::from_registry().do_send(Unsubscribe { id: self.id, topic })
+                    }
+                    _ => ctx.text(r#"{"error":"Invalid Action"}"#),
+                }
+            }
+            ws::Message::Close(_) => {
+                ctx.stop();
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// render digest email, in text and html
+
+use crate::mail::{escape_html, Mail};
+use crate::model::digest::Digest;
+use crate::util::share::site_url;
+
+// max chars of reply excerpt
+const EXCERPT_LEN: usize = 140;
+
+pub fn render_digest(to: &str, uname: &str, digest: &Digest) -> Mail {
+    let site = site_url();
+
+    let mut text = format!("Hi {}, here is what's new for you.\n", uname);
+    let mut html = format!(
+        "<p>Hi {}, here is what's new for you.</p>\n",
+        escape_html(uname)
+    );
+
+    if !digest.tag_ruts.is_empty() {
+        text.push_str("\nNew ruts in your starred tags:\n");
+        html.push_str("<h3>New ruts in your starred tags</h3>\n<ul>\n");
+        for r in digest.tag_ruts.iter() {
+            let link = format!("{}/r/{}", site, r.slug);
+            text.push_str(&format!("- {} by {}\n  {}\n", r.title, r.uname, link));
+            html.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> by {}</li>\n",
+                escape_html(&link),
+                escape_html(&r.title),
+                escape_html(&r.uname)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !digest.rut_items.is_empty() {
+        text.push_str("\nNew items in your starred ruts:\n");
+        html.push_str("<h3>New items in your starred ruts</h3>\n<ul>\n");
+        for (r, i) in digest.rut_items.iter() {
+            let r_link = format!("{}/r/{}", site, r.slug);
+            let i_link = format!("{}/item/{}", site, i.slug);
+            text.push_str(&format!("- {} in {}\n  {}\n", i.title, r.title, i_link));
+            html.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> in <a href=\"{}\">{}</a></li>\n",
+                escape_html(&i_link),
+                escape_html(&i.title),
+                escape_html(&r_link),
+                escape_html(&r.title)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !digest.replies.is_empty() {
+        text.push_str("\nReplies to you:\n");
+        html.push_str("<h3>Replies to you</h3>\n<ul>\n");
+        for e in digest.replies.iter() {
+            let excerpt: String = e.content.chars().take(EXCERPT_LEN).collect();
+            text.push_str(&format!("- {}: {}\n", e.uname, excerpt));
+            html.push_str(&format!(
+                "<li><b>{}</b>: {}</li>\n",
+                escape_html(&e.uname),
+                escape_html(&excerpt)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    let setting_link = format!("{}/settings", site);
+    text.push_str(&format!(
+        "\nChange the digest frequency at {}\n",
+        setting_link
+    ));
+    html.push_str(&format!(
+        "<p><small><a href=\"{}\">Change the digest frequency</a></small></p>\n",
+        escape_html(&setting_link)
+    ));
+
+    Mail {
+        to: to.to_owned(),
+        subject: "Your digest on RutHub".to_owned(),
+        text,
+        html,
+    }
+}
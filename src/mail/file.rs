@@ -0,0 +1,55 @@
+// file outbox mailer, write each email as .eml
+
+use chrono::Utc;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::errors::ServiceError;
+use crate::mail::{Mail, Mailer};
+
+pub struct FileMailer {
+    dir: PathBuf,
+    from: String,
+}
+
+impl FileMailer {
+    pub fn new(dir: String, from: String) -> Self {
+        FileMailer {
+            dir: PathBuf::from(dir),
+            from,
+        }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&mut self, mail: &Mail) -> Result<(), ServiceError> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| ServiceError::InternalServerError(format!("outbox: {}", e)))?;
+
+        let boundary = format!("{}", uuid::Uuid::new_v4());
+        let eml = format!(
+            "From: {from}\r\nTo: {to}\r\nSubject: {subject}\r\nDate: {date}\r\n\
+             MIME-Version: 1.0\r\n\
+             Content-Type: multipart/alternative; boundary=\"{b}\"\r\n\r\n\
+             --{b}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{text}\r\n\
+             --{b}\r\nContent-Type: text/html; charset=utf-8\r\n\r\n{html}\r\n\
+             --{b}--\r\n",
+            from = self.from,
+            to = mail.to,
+            subject = mail.subject,
+            date = Utc::now().to_rfc2822(),
+            b = boundary,
+            text = mail.text,
+            html = mail.html,
+        );
+        let file_name = format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%d%H%M%S%f"),
+            mail.to.replace(|c: char| !c.is_alphanumeric(), "_"),
+        );
+        fs::write(self.dir.join(file_name), eml)
+            .map_err(|e| ServiceError::InternalServerError(format!("outbox: {}", e)))?;
+
+        Ok(())
+    }
+}
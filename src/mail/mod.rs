@@ -0,0 +1,56 @@
+// mail mod
+
+// send email via a pluggable mailer:
+// smtp, or file outbox for dev and test, per env MAILER=smtp|file
+
+pub mod digest;
+pub mod file;
+pub mod smtp;
+
+use crate::errors::ServiceError;
+
+// email with text and html alternative
+#[derive(Clone, Debug)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+pub trait Mailer: Send {
+    fn send(&mut self, mail: &Mail) -> Result<(), ServiceError>;
+}
+
+// get mailer per env, file outbox as default
+pub fn init_mailer() -> Box<dyn Mailer> {
+    let from = dotenv::var("MAIL_FROM").unwrap_or_else(|_| "noreply@ruthub.com".into());
+    match dotenv::var("MAILER").unwrap_or_default().as_str() {
+        "smtp" => Box::new(smtp::SmtpMailer::new(
+            dotenv::var("SMTP_HOST").expect("SMTP_HOST must be set"),
+            dotenv::var("SMTP_USER").unwrap_or_default(),
+            dotenv::var("SMTP_PASSWORD").unwrap_or_default(),
+            from,
+        )),
+        _ => Box::new(file::FileMailer::new(
+            dotenv::var("MAIL_OUTBOX").unwrap_or_else(|_| "outbox".into()),
+            from,
+        )),
+    }
+}
+
+// escape text in html
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,47 @@
+// smtp mailer
+
+use lettre::smtp::authentication::Credentials;
+use lettre::{SmtpClient, SmtpTransport, Transport};
+use lettre_email::EmailBuilder;
+
+use crate::errors::ServiceError;
+use crate::mail::{Mail, Mailer};
+
+pub struct SmtpMailer {
+    transport: SmtpTransport,
+    from: String,
+}
+
+impl SmtpMailer {
+    pub fn new(host: String, user: String, password: String, from: String) -> Self {
+        let client = SmtpClient::new_simple(&host).expect("Failed to create smtp client.");
+        let client = if user != "" {
+            client.credentials(Credentials::new(user, password))
+        } else {
+            client
+        };
+
+        SmtpMailer {
+            transport: client.transport(),
+            from,
+        }
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&mut self, mail: &Mail) -> Result<(), ServiceError> {
+        let email = EmailBuilder::new()
+            .to(mail.to.as_str())
+            .from(self.from.as_str())
+            .subject(mail.subject.as_str())
+            .alternative(mail.html.as_str(), mail.text.as_str())
+            .build()
+            .map_err(|e| ServiceError::InternalServerError(format!("email: {}", e)))?;
+
+        self.transport
+            .send(email.into())
+            .map_err(|e| ServiceError::InternalServerError(format!("smtp: {}", e)))?;
+
+        Ok(())
+    }
+}
@@ -30,12 +30,19 @@ mod api;
 mod bot;
 mod db;
 mod errors;
+mod hub;
+mod mail;
 mod model;
 mod schema;
+mod score;
+mod search;
+mod task;
 mod util;
 
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+
 // This is db executor actor
-pub struct Dba(pub Pool<ConnectionManager<PgConnection>>);
+pub struct Dba(pub DbPool);
 
 impl Actor for Dba {
     type Context = SyncContext<Self>;
@@ -43,17 +50,20 @@ impl Actor for Dba {
 
 pub type DbAddr = Addr<Dba>;
 
-pub fn init_dba() -> DbAddr {
+pub fn init_pool() -> DbPool {
     let db_url = dotenv::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let manager = ConnectionManager::<PgConnection>::new(db_url);
     let cpu_num = num_cpus::get();
     let pool_num = std::cmp::max(10, cpu_num * 2 + 1) as u32;
     // p_num subject to c_num??
-    let conn = Pool::builder()
+    Pool::builder()
         .max_size(pool_num)
         .build(manager)
-        .expect("Failed to create pool.");
+        .expect("Failed to create pool.")
+}
 
+pub fn init_dba(conn: DbPool) -> DbAddr {
+    let cpu_num = num_cpus::get();
     SyncArbiter::start(cpu_num * 2 + 1, move || Dba(conn.clone()))
 }
 
@@ -79,16 +89,42 @@ pub fn init_fern_logger() -> Result<(), fern::InitError> {
 fn main() -> std::io::Result<()> {
     // init logger
     init_fern_logger().unwrap_or_default();
+    // `rut-server-rust reindex`: rebuild search index from db, then exit,
+    // run with the server stopped, which holds the index writer lock
+    if std::env::args().nth(1).as_ref().map(String::as_str) == Some("reindex") {
+        println!("Reindexing, the server must be stopped to release the index lock");
+        let conn = init_pool().get().expect("Failed to get db connection");
+        let doc_count = search::backend()
+            .reindex(&conn)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        println!("Reindexed {} docs", doc_count);
+        return Ok(());
+    }
     // new runtime
     let sys = actix_rt::System::new("rut-server-rust");
     // init actor
-    let addr: DbAddr = init_dba();
+    let pool = init_pool();
+    let addr: DbAddr = init_dba(pool.clone());
+    // webhook deliverer, blocking http
+    let deliver = task::deliver::init_deliverer(pool.clone());
+    // email digest sender
+    let digest = task::digest::init_digester(pool);
+    // start periodic task
+    task::Scheduler {
+        db: addr.clone(),
+        deliver: deliver.clone(),
+        digest,
+    }
+    .start();
+    // start broadcast hub on system arbiter
+    hub::Hub::from_registry();
 
     let bind_host = dotenv::var("BIND_ADDRESS").unwrap_or("127.0.0.1:8083".to_string());
     // config Server, App, AppState, middleware, service
     HttpServer::new(move || {
         App::new()
             .data(addr.clone())
+            .data(deliver.clone())
             .wrap(Logger::default())
             .wrap(Cors::default())
             // everything under '/api/' route
@@ -103,6 +139,11 @@ fn main() -> std::io::Result<()> {
                     resource("/signup")
                         .route(post().to_async(api::auth::signup))
                 )
+                // real-time updates, ?token=jwt
+                .service(
+                    resource("/ws")
+                        .route(get().to(api::ws::ws_index))
+                )
                 // get / update user, change password
                 .service(
                     resource("/users/{uname}")
@@ -110,6 +151,61 @@ fn main() -> std::io::Result<()> {
                         .route(post().to_async(api::auth::update))
                         .route(put().to_async(api::auth::change_psw))
                 )
+                .service(
+                    resource("/users/{uname}/mentions") // ?page=
+                        .route(get().to_async(api::mention::get_mentions))
+                )
+                .service(
+                    resource("/followuser/{fname}/{action:[0|1]}")
+                        .route(post().to_async(api::auth::follow_user))
+                )
+                .service(
+                    resource("/notifications") // ?page=&flag=unread|all
+                        .route(get().to_async(api::notify::get_list))
+                )
+                .service(
+                    resource("/notifications/count")
+                        .route(get().to_async(api::notify::get_count))
+                )
+                .service(
+                    resource("/notifications/{nid}/{flag:read|unread}") // nid=all to mark all
+                        .route(post().to_async(api::notify::mark_read))
+                )
+                .service(
+                    resource("/notifysettings")
+                        .route(get().to_async(api::notify::get_settings))
+                        .route(put().to_async(api::notify::update_setting))
+                )
+                .service(
+                    resource("/digestsettings")
+                        .route(get().to_async(api::digest::get_setting))
+                        .route(put().to_async(api::digest::update_setting))
+                )
+                .service(
+                    resource("/webhooks")
+                        .route(get().to_async(api::webhook::get_list))
+                        .route(post().to_async(api::webhook::new))
+                )
+                .service(
+                    resource("/webhooks/{wid}")
+                        .route(delete().to_async(api::webhook::delete))
+                )
+                .service(
+                    resource("/webhooks/{wid}/deliveries") // ?page=
+                        .route(get().to_async(api::webhook::get_deliveries))
+                )
+                .service(
+                    resource("/webhooks/{wid}/test")
+                        .route(post().to_async(api::webhook::test))
+                )
+                .service(
+                    resource("/feeds/{per}/{perid}") // per=rut|tag|user|index, ?format=atom|rss
+                        .route(get().to_async(api::feed::get_feed))
+                )
+                .service(
+                    resource("/search") // ?q=&type=rut|item|tag|user|etc&tag=&user=&item=&category=&page=
+                        .route(get().to_async(api::search::search))
+                )
                 .service(
                     resource("/ruts")
                         .route(post().to_async(api::rut::new))
@@ -121,6 +217,44 @@ fn main() -> std::io::Result<()> {
                         .route(post().to_async(api::rut::update)) // can be del, per frontend
                                                                     //.route(delete().to_async(api::rut::delete))
                 )
+                .service(
+                    resource("/suggesttags/rut/{rutid}")
+                        .route(get().to_async(api::tag::suggest_rut_tags))
+                )
+                .service(
+                    resource("/relatedruts/{rutid}")
+                        .route(get().to_async(api::rut::get_related))
+                )
+                .service(
+                    resource("/rutcollabs/{rutid}")
+                        .route(get().to_async(api::collab::get_list))
+                        .route(post().to_async(api::collab::invite))
+                )
+                .service(
+                    resource("/acceptcollab/{rutid}")
+                        .route(post().to_async(api::collab::accept))
+                )
+                .service(
+                    resource("/rutcollabs/{rutid}/{uname}")
+                        .route(delete().to_async(api::collab::remove))
+                )
+                .service(
+                    resource("/rutsuggests/{rutid}") // ?page=&status=
+                        .route(get().to_async(api::suggest::get_list))
+                        .route(post().to_async(api::suggest::new))
+                )
+                .service(
+                    resource("/suggests/{sid}/accept")
+                        .route(post().to_async(api::suggest::accept))
+                )
+                .service(
+                    resource("/suggests/{sid}/decline")
+                        .route(post().to_async(api::suggest::decline))
+                )
+                .service(
+                    resource("/ruts/index/{mode}") // ?page=p&window=week|month|all
+                        .route(get().to_async(api::rut::get_index))
+                )
                 .service(
                     resource("/ruts/{per}/{perid}") // ?page=p&flag=create|star&kw= fr=
                         .route(get().to_async(api::rut::get_list))
@@ -144,6 +278,18 @@ fn main() -> std::io::Result<()> {
                         .route(post().to_async(api::item::update)) // can be del, per frontend
                                                                     // .route(delete().to_async(api::item::delete))
                 )
+                .service(
+                    resource("/suggesttags/item/{itemid}")
+                        .route(get().to_async(api::tag::suggest_item_tags))
+                )
+                .service(
+                    resource("/similaritems/{itemid}")
+                        .route(get().to_async(api::recommend::get_similar))
+                )
+                .service(
+                    resource("/recommendations") // ?page=
+                        .route(get().to_async(api::recommend::get_recommendations))
+                )
                 .service(
                     resource("/items/{per}/{id}") //?page=p&flag=&kw=url_base64&fr= // special per-url
                         .route(get().to_async(api::item::get_list))
@@ -176,6 +322,10 @@ fn main() -> std::io::Result<()> {
                         .route(put().to_async(api::tag::update))
                         .route(post().to_async(api::tag::new))
                 )
+                .service(
+                    resource("/relatedtags/{tname}")
+                        .route(get().to_async(api::tag::get_related))
+                )
                 .service(
                     resource("/tags/{per}/{id}")
                         .route(get().to_async(api::tag::get_list))
@@ -188,6 +338,14 @@ fn main() -> std::io::Result<()> {
                     resource("/totag/{action:[0|1]}") // tag rut|item|etc
                         .route(post().to_async(api::tag::tag_any))
                 )
+                .service(
+                    resource("/ruttags/{rutid}/{tname}/{action:lock|unlock|remove}")
+                        .route(post().to_async(api::tag::moderate_rut_tag))
+                )
+                .service(
+                    resource("/iftagvote/{vote_to}/{to_id}")
+                        .route(get().to_async(api::tag::tag_vote_status))
+                )
                 .service(
                     resource("/startag/{tname}/{action:[0|1]}/{note}")
                         .route(get().to_async(api::tag::star_or_unstar))
@@ -196,14 +354,31 @@ fn main() -> std::io::Result<()> {
                     resource("/ifstartag/{tname}")
                         .route(get().to_async(api::tag::star_status))
                 )
+                .service(
+                    resource("/vote/{vote_to}/{to_id}/{updown:-1|0|1}")
+                        .route(post().to_async(api::vote::vote))
+                )
+                .service(
+                    resource("/ifvote/{vote_to}/{to_id}")
+                        .route(get().to_async(api::vote::vote_status))
+                )
                 .service(
                     resource("/etcs")
                         .route(post().to_async(api::etc::new))
+                        .route(put().to_async(api::etc::update))
+                )
+                .service(
+                    resource("/etcs/{etcid}")
+                        .route(delete().to_async(api::etc::delete))
                 )
                 .service(
                     resource("/etcs/{per}/{perid}")
                         .route(get().to_async(api::etc::get_list))
                 )
+                .service(
+                    resource("/etcs/{per}/{perid}/tree") // ?page=p&depth=&sort=time|vote
+                        .route(get().to_async(api::etc::get_tree))
+                )
                 .default_service(route().to(|| HttpResponse::NotFound()))
             )
     })
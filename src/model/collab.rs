@@ -0,0 +1,100 @@
+// collaborator typed model and msg handler
+
+use actix::Message;
+use actix_web::{error, Error};
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+use crate::model::msg::{CollabListMsg, CollabMsg, Msg};
+use crate::model::{re_test_name, Validate};
+use crate::schema::rutcollabs;
+
+// roles of collaborator:
+// editor: collect item, edit or delete collect, update rut; viewer: view private rut
+pub const COLLAB_ROLES: [&str; 2] = ["editor", "viewer"];
+
+// use to build select query
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "rutcollabs"]
+pub struct RutCollab {
+    pub id: String,
+    pub rut_id: String,
+    pub uname: String, // who invited
+    pub role: String,  // editor|viewer
+    pub accepted: bool,
+    pub invite_by: String,
+    pub invite_at: NaiveDateTime,
+    pub accept_at: Option<NaiveDateTime>,
+}
+
+// as msg to invite a user, or change the role, by rut owner
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct InviteCollab {
+    #[serde(default)]
+    pub rut_id: String,
+    pub uname: String,
+    pub role: String,
+    #[serde(default)]
+    pub invite_by: String,
+}
+
+impl Message for InviteCollab {
+    type Result = Result<CollabMsg, ServiceError>;
+}
+
+impl Validate for InviteCollab {
+    fn validate(&self) -> Result<(), Error> {
+        let check = re_test_name(&self.uname) && COLLAB_ROLES.contains(&self.role.trim());
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
+
+// as msg to accept the invitation, by invitee
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AcceptCollab {
+    pub rut_id: String,
+    pub uname: String,
+}
+
+impl Message for AcceptCollab {
+    type Result = Result<CollabMsg, ServiceError>;
+}
+
+// as msg to remove a collaborator by rut owner, or leave by self
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RemoveCollab {
+    pub rut_id: String,
+    pub uname: String,
+    pub action_by: String, // to check permission
+}
+
+impl Message for RemoveCollab {
+    type Result = Result<Msg, ServiceError>;
+}
+
+// as msg to get the collaborators of rut
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryCollabs {
+    pub rut_id: String,
+    pub viewer: String, // per visibility of rut
+}
+
+impl Message for QueryCollabs {
+    type Result = Result<CollabListMsg, ServiceError>;
+}
+
+// as msg to check if user can view a rut, e.g. before subscribe its events
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CheckView {
+    pub rut_id: String,
+    pub uname: String,
+}
+
+impl Message for CheckView {
+    type Result = Result<bool, ServiceError>;
+}
@@ -0,0 +1,80 @@
+// digest typed model and msg handler
+
+use actix::Message;
+use actix_web::{error, Error};
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+use crate::model::etc::Etc;
+use crate::model::item::Item;
+use crate::model::msg::{DigestSettingMsg, Msg};
+use crate::model::rut::Rut;
+use crate::model::Validate;
+use crate::schema::digestsettings;
+
+// frequency of digest
+pub const DIGEST_FREQUENCY: [&str; 3] = ["weekly", "daily", "off"];
+
+// user's preference of email digest, off if no record, opt in
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "digestsettings"]
+pub struct DigestSetting {
+    pub id: String,
+    pub uname: String,
+    pub frequency: String, // weekly|daily|off
+    pub last_sent: Option<NaiveDateTime>,
+}
+
+// new activity since last digest
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct Digest {
+    pub tag_ruts: Vec<Rut>,          // new ruts in starred tags
+    pub rut_items: Vec<(Rut, Item)>, // new items in starred ruts
+    pub replies: Vec<Etc>,           // replies to user's etcs
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.tag_ruts.is_empty() && self.rut_items.is_empty() && self.replies.is_empty()
+    }
+}
+
+// as msg to get digest preference
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryDigestSetting {
+    pub uname: String,
+}
+
+impl Message for QueryDigestSetting {
+    type Result = Result<DigestSettingMsg, ServiceError>;
+}
+
+// as msg to set digest preference
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UpdateDigestSetting {
+    pub uname: String,
+    pub frequency: String,
+}
+
+impl Message for UpdateDigestSetting {
+    type Result = Result<DigestSettingMsg, ServiceError>;
+}
+
+impl Validate for UpdateDigestSetting {
+    fn validate(&self) -> Result<(), Error> {
+        let check = DIGEST_FREQUENCY.contains(&self.frequency.trim());
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
+
+// as msg to send the digest in due, to task::digest
+pub struct SendDigests;
+
+impl Message for SendDigests {
+    type Result = Result<Msg, ServiceError>;
+}
@@ -5,12 +5,13 @@ use actix_web::{error, Error};
 use chrono::NaiveDateTime;
 
 use crate::errors::ServiceError;
-use crate::model::msg::{EtcListMsg, EtcMsg, Msg};
+use crate::model::msg::{EtcListMsg, EtcMsg, EtcTreeMsg, Msg};
 use crate::model::{re_test_url, test_len_limit, Validate, TAG_LEN};
 use crate::schema::etcs;
 
 // use to build select query
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[derive(QueryableByName)] // to load from raw sql, in search
 #[table_name = "etcs"]
 pub struct Etc {
     pub id: String,
@@ -21,7 +22,26 @@ pub struct Etc {
     pub item_id: String,
     pub tname: String,
     pub uname: String, // who post
-    pub vote: i32,
+    pub vote: i32,     // net of up/down votes
+    pub reply_count: i32,
+    pub edited_at: Option<NaiveDateTime>,
+    pub deleted: bool, // as tombstone if have replies
+    pub kind: String,  // comment|excerpt|note|review
+    pub page: Option<i32>,
+    pub chapter: String,
+    pub location: String,     // e.g. kindle location
+    pub percent: Option<f32>, // position in item, 0-100
+    pub content_html: String,
+}
+
+// kinds of etc
+pub const ETC_KINDS: [&str; 4] = ["comment", "excerpt", "note", "review"];
+
+// etc with its replies, as node of comment tree
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EtcNode {
+    pub etc: Etc,
+    pub replies: Vec<EtcNode>,
 }
 
 // as msg in create new
@@ -31,6 +51,21 @@ pub struct PostEtc {
     pub post_to: String,
     pub to_id: String,
     pub uname: String,
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    // location of excerpt in item, optional
+    #[serde(default)]
+    pub page: Option<i32>,
+    #[serde(default)]
+    pub chapter: String,
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub percent: Option<f32>,
+}
+
+fn default_kind() -> String {
+    "comment".to_owned()
 }
 
 impl Message for PostEtc {
@@ -40,7 +75,14 @@ impl Message for PostEtc {
 impl Validate for PostEtc {
     fn validate(&self) -> Result<(), Error> {
         let check_len = test_len_limit(&self.content, 1, 512);
-        let check = check_len;
+        let check_kind = ETC_KINDS.contains(&self.kind.trim());
+        // excerpt must be attached to an item
+        let check_excerpt = self.kind.trim() != "excerpt" || self.post_to.trim() == "item";
+        let check_loc = self.page.map_or(true, |p| p > 0)
+            && self.percent.map_or(true, |c| c >= 0.0 && c <= 100.0)
+            && test_len_limit(&self.chapter, 0, 256)
+            && test_len_limit(&self.location, 0, 64);
+        let check = check_len && check_kind && check_excerpt && check_loc;
 
         if check {
             Ok(())
@@ -57,20 +99,61 @@ pub struct QueryEtcs {
     pub per: String,
     pub perid: String,
     pub page: i32,
+    pub sort: String,   // time|score
+    pub kind: String,   // comment|excerpt|note|review, empty for all
+    pub viewer: String, // per visibility of rut
 }
 
 impl Message for QueryEtcs {
     type Result = Result<EtcListMsg, ServiceError>;
 }
 
-// todo
+// as msg to get etc tree of rut|item|tag|petc
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryEtcTree {
+    pub per: String,
+    pub perid: String,
+    pub depth: i32,     // levels, 1 as top only
+    pub sort: String,   // time|vote
+    pub page: i32,      // paging the top level
+    pub viewer: String, // per visibility of rut
+}
+
+impl Message for QueryEtcTree {
+    type Result = Result<EtcTreeMsg, ServiceError>;
+}
+
+// as msg in edit etc
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UpdateEtc {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    pub uname: String, // to check permission, per auth
+}
+
+impl Message for UpdateEtc {
+    type Result = Result<EtcMsg, ServiceError>;
+}
+
+impl Validate for UpdateEtc {
+    fn validate(&self) -> Result<(), Error> {
+        let check_len = test_len_limit(&self.content, 1, 512);
+        let check = check_len;
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input(1-512)"))
+        }
+    }
+}
+
 // as msg to del etc
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct DelEtc {
     pub etc_id: String,
-    pub rut_id: String,  // to update rut after del
-    pub item_id: String, // to update item after del
-    pub uname: String,   // to check permission
+    pub uname: String, // to check permission
 }
 
 impl Message for DelEtc {
@@ -0,0 +1,37 @@
+// feed typed model and msg handler
+
+use actix::Message;
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+
+// feed of rut|tag|user|index, to render as atom or rss
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Feed {
+    pub id: String,
+    pub title: String,
+    pub link: String, // page on site
+    pub updated: NaiveDateTime,
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FeedEntry {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub author: String,
+    pub summary: String,
+    pub updated: NaiveDateTime,
+}
+
+// as msg to get feed
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryFeed {
+    pub per: String, // rut|tag|user|index
+    pub perid: String,
+}
+
+impl Message for QueryFeed {
+    type Result = Result<Feed, ServiceError>;
+}
@@ -8,10 +8,12 @@ use crate::errors::ServiceError;
 use crate::model::msg::{CollectMsg, CollectsMsg, ItemListMsg, ItemMsg, Msg, StarItemMsg};
 use crate::model::{re_test_url, test_len_limit, Validate, TITLE_LEN, UIID_LEN};
 use crate::schema::{collects, items, staritems};
+use crate::util::md::md_to_html;
 use crate::util::share::gen_slug;
 
 // use to build select query
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[derive(QueryableByName)] // to load from raw sql, in search
 #[table_name = "items"]
 pub struct Item {
     pub id: String,
@@ -30,6 +32,7 @@ pub struct Item {
     pub done_count: i32, // num of who done
     pub vote: i32,       //  cal per rut, done, etc
     pub slug: String,    // to do
+    pub score: i32,      // net of up/down votes
 }
 
 // Item's constructor
@@ -52,6 +55,7 @@ impl Item {
             done_count: 0,
             vote: 0,
             slug,
+            score: 0,
         }
     }
 }
@@ -196,7 +200,7 @@ pub enum QueryItems {
     Title(String),
     ItemUrl(String),
     RutID(String),
-    TagID(String),
+    TagID(String, String),              // tname, sort: count|score
     UserID(String, i16, i32),           // (uname, flag, paging)
     KeyID(String, String, String, i32), // keyword, per, perid(uname|tname), paging
 }
@@ -232,6 +236,7 @@ pub struct Collect {
     // pub spoiler: bool,  // to do but
     pub uname: String,
     pub collect_at: NaiveDateTime,
+    pub content_html: String,
 }
 
 // Collect's constructor
@@ -242,6 +247,7 @@ impl Collect {
             rut_id: c.rut_id,
             item_id: c.item_id,
             item_order: i_order,
+            content_html: md_to_html(&c.content),
             content: c.content,
             uname: c.uname,
             collect_at: Utc::now().naive_utc(),
@@ -294,6 +300,7 @@ impl Message for DelCollect {
 pub struct QueryCollect {
     pub collect_id: String,
     pub action: String, // get|delete
+    pub viewer: String, // per visibility of rut
 }
 
 impl Message for QueryCollect {
@@ -303,9 +310,9 @@ impl Message for QueryCollect {
 // as msg in collect list per rutid or itemid
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum QueryCollects {
-    RutID(String),
-    ItemID(String, i32), // id, paging
-    UserID(String, i32), // id, paging
+    RutID(String, String),       // id, viewer
+    ItemID(String, i32),         // id, paging
+    UserID(String, String, i32), // id, viewer, paging
 }
 
 impl Message for QueryCollects {
@@ -0,0 +1,34 @@
+// mention typed model and msg handler
+
+use actix::Message;
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+use crate::model::msg::MentionListMsg;
+use crate::schema::mentions;
+
+// @uname in etc or collect content
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "mentions"]
+pub struct Mention {
+    pub id: String,
+    pub uname: String,      // who is mentioned
+    pub by_uname: String,   // who mention
+    pub mention_to: String, // etc|collect
+    pub to_id: String,
+    pub rut_id: String,
+    pub item_id: String,
+    pub mention_at: NaiveDateTime,
+}
+
+// as msg to get mentions of a user
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryMentions {
+    pub uname: String,
+    pub page: i32,
+    pub viewer: String, // per visibility of rut
+}
+
+impl Message for QueryMentions {
+    type Result = Result<MentionListMsg, ServiceError>;
+}
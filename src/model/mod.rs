@@ -1,20 +1,48 @@
 // type model mod
 
+pub mod collab;
+pub mod digest;
 pub mod etc;
+pub mod feed;
 pub mod item;
+pub mod mention;
+pub mod notify;
 pub mod msg;
+pub mod recommend;
 pub mod rut;
+pub mod search;
+pub mod suggest;
 pub mod tag;
 pub mod user;
+pub mod vote;
+pub mod webhook;
 
+use actix::Message;
 use actix_web::Error;
 use regex::Regex;
 
+use crate::errors::ServiceError;
+use crate::model::msg::Msg;
+
 // for validate request content input
 pub trait Validate {
     fn validate(&self) -> Result<(), Error>;
 }
 
+// as msg to render html of content not yet rendered, by task
+pub struct RenderHtml;
+
+impl Message for RenderHtml {
+    type Result = Result<Msg, ServiceError>;
+}
+
+// as msg to re-cal the vote of ruts, items, tags per the counters, by task
+pub struct RecomputeVotes;
+
+impl Message for RecomputeVotes {
+    type Result = Result<Msg, ServiceError>;
+}
+
 // re test
 // for re test uname
 pub fn re_test_name(text: &str) -> bool {
@@ -51,6 +79,22 @@ pub fn re_test_img_url(text: &str) -> bool {
     RE.is_match(text)
 }
 
+// for extract @uname in content, dedup, w/o email
+pub fn re_get_mentions(text: &str) -> Vec<String> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"(?:^|[^\w@])@([\w-]{3,42})").unwrap(); // let fail in test
+    }
+    let mut names: Vec<String> = Vec::new();
+    for cap in RE.captures_iter(text) {
+        let name = cap[1].to_owned();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
 pub fn replace_sep(text: &str, rep: &str) -> String {
     lazy_static! {
         static ref RE: Regex =
@@ -1,10 +1,19 @@
 // typed-msg  model
 
-use crate::model::etc::Etc;
+use std::collections::HashMap;
+
+use crate::model::collab::RutCollab;
+use crate::model::digest::DigestSetting;
+use crate::model::etc::{Etc, EtcNode};
 use crate::model::item::{Collect, Item};
-use crate::model::rut::Rut;
-use crate::model::tag::Tag;
+use crate::model::mention::Mention;
+use crate::model::notify::{Notification, NotifySetting};
+use crate::model::recommend::RecItem;
+use crate::model::rut::{RelatedRut, Rut};
+use crate::model::suggest::RutSuggest;
+use crate::model::tag::{RelatedTag, SuggestedTag, Tag};
 use crate::model::user::{CheckUser, User};
+use crate::model::webhook::{Delivery, Webhook};
 
 // general response msg struct
 #[derive(Deserialize, Serialize, Debug)]
@@ -46,6 +55,17 @@ pub struct RutListMsg {
     pub message: String,
     pub ruts: Vec<Rut>,
     pub count: usize,
+    pub highlights: HashMap<String, String>, // id: marked headline, in keyword search
+}
+
+// result struct in response related ruts
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RelatedRutsMsg {
+    pub status: i32,
+    pub message: String,
+    pub ruts: Vec<RelatedRut>,
+    pub count: usize,
+    pub dup_of: Option<RelatedRut>, // the rut whose items heavily overlap, if any
 }
 
 // result struct in response an item
@@ -63,6 +83,8 @@ pub struct ItemListMsg {
     pub message: String,
     pub items: Vec<Item>,
     pub count: usize,
+    pub highlights: HashMap<String, String>, // id: marked headline, in keyword search
+    pub suggestion: String,                  // did you mean, in fuzzy lookup
 }
 
 // result struct in respon the status of star item
@@ -107,6 +129,24 @@ pub struct TagListMsg {
     pub count: usize,
 }
 
+// result struct in response related tags
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RelatedTagsMsg {
+    pub status: i32,
+    pub message: String,
+    pub tags: Vec<RelatedTag>,
+    pub count: usize,
+}
+
+// result struct in response suggested tags
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SuggestTagsMsg {
+    pub status: i32,
+    pub message: String,
+    pub tags: Vec<SuggestedTag>,
+    pub count: usize,
+}
+
 // result struct in response etc
 #[derive(Deserialize, Serialize, Debug)]
 pub struct EtcMsg {
@@ -124,6 +164,100 @@ pub struct EtcListMsg {
     pub count: usize,
 }
 
+// result struct in response etc tree
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EtcTreeMsg {
+    pub status: i32,
+    pub message: String,
+    pub etcs: Vec<EtcNode>,
+    pub count: usize, // total of top level
+}
+
+// result struct in response mention list
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MentionListMsg {
+    pub status: i32,
+    pub message: String,
+    pub mentions: Vec<Mention>,
+    pub count: usize,
+}
+
+// result struct in response notification list
+#[derive(Deserialize, Serialize, Debug)]
+pub struct NotifyListMsg {
+    pub status: i32,
+    pub message: String,
+    pub notifications: Vec<Notification>,
+    pub count: usize,
+}
+
+// result struct in response unread count
+#[derive(Deserialize, Serialize, Debug)]
+pub struct NotifyCountMsg {
+    pub status: i32,
+    pub message: String,
+    pub count: i64,
+}
+
+// result struct in response notification preference
+#[derive(Deserialize, Serialize, Debug)]
+pub struct NotifySettingsMsg {
+    pub status: i32,
+    pub message: String,
+    pub settings: Vec<NotifySetting>,
+}
+
+// result struct in response vote status and net score
+#[derive(Deserialize, Serialize, Debug)]
+pub struct VoteMsg {
+    pub status: i32,
+    pub message: String,
+    pub updown: i16, // vote of the user: 1|-1|0
+    pub score: i32,  // net score of the target
+}
+
+// result struct in response digest preference
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DigestSettingMsg {
+    pub status: i32,
+    pub message: String,
+    pub setting: DigestSetting,
+}
+
+// result struct in response webhook
+#[derive(Deserialize, Serialize, Debug)]
+pub struct WebhookMsg {
+    pub status: i32,
+    pub message: String,
+    pub webhook: Webhook,
+}
+
+// result struct in response webhook list
+#[derive(Deserialize, Serialize, Debug)]
+pub struct WebhookListMsg {
+    pub status: i32,
+    pub message: String,
+    pub webhooks: Vec<Webhook>,
+    pub count: usize,
+}
+
+// result struct in response delivery
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DeliveryMsg {
+    pub status: i32,
+    pub message: String,
+    pub delivery: Delivery,
+}
+
+// result struct in response delivery log
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DeliveryListMsg {
+    pub status: i32,
+    pub message: String,
+    pub deliveries: Vec<Delivery>,
+    pub count: usize,
+}
+
 // todo
 // respon the status of star rut, follow tag, etc.
 #[derive(Deserialize, Serialize, Debug)]
@@ -132,3 +266,61 @@ pub struct StarStatusMsg {
     pub message: String, // star | Unstar
     pub count: i32,      // star num
 }
+
+// result struct in response search, grouped per type
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SearchMsg {
+    pub status: i32,
+    pub message: String,
+    pub ruts: Vec<Rut>,
+    pub items: Vec<Item>,
+    pub tags: Vec<Tag>,
+    pub users: Vec<CheckUser>,
+    pub etcs: Vec<Etc>,
+    pub facets: HashMap<String, i64>, // type: count of hits
+    pub highlights: HashMap<String, String>, // id: marked headline
+    pub count: usize,
+}
+
+// result struct in response similar or recommended items
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecItemsMsg {
+    pub status: i32,
+    pub message: String,
+    pub items: Vec<RecItem>,
+    pub count: usize,
+}
+
+// result struct in response a collaborator
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CollabMsg {
+    pub status: i32,
+    pub message: String,
+    pub collab: RutCollab,
+}
+
+// result struct in response collaborators of rut
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CollabListMsg {
+    pub status: i32,
+    pub message: String,
+    pub collabs: Vec<RutCollab>,
+    pub count: usize,
+}
+
+// result struct in response a suggestion
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SuggestMsg {
+    pub status: i32,
+    pub message: String,
+    pub suggest: RutSuggest,
+}
+
+// result struct in response suggestions to rut
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SuggestListMsg {
+    pub status: i32,
+    pub message: String,
+    pub suggests: Vec<RutSuggest>,
+    pub count: usize,
+}
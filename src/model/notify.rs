@@ -0,0 +1,118 @@
+// notification typed model and msg handler
+
+use actix::Message;
+use actix_web::{error, Error};
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+use crate::model::msg::{Msg, NotifyCountMsg, NotifyListMsg, NotifySettingsMsg};
+use crate::model::Validate;
+use crate::schema::{notifications, notifysettings};
+
+// actions to notify
+pub const NOTIFY_ACTIONS: [&str; 11] = [
+    "star_rut",
+    "comment_rut",
+    "collect_rut",
+    "reply_etc",
+    "mention",
+    "follow",
+    "invite_collab",
+    "accept_collab",
+    "suggest_rut",
+    "accept_suggest",
+    "decline_suggest",
+];
+
+// use to build select query
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "notifications"]
+pub struct Notification {
+    pub id: String,
+    pub uname: String,  // who to notify
+    pub actor: String,  // who act
+    pub action: String, // one of NOTIFY_ACTIONS
+    pub obj: String,    // rut|etc|collect|user
+    pub objid: String,
+    pub content: String, // excerpt of content, or title
+    pub is_read: bool,
+    pub notify_at: NaiveDateTime,
+}
+
+// user's preference per action, enabled if no record
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "notifysettings"]
+pub struct NotifySetting {
+    pub id: String,
+    pub uname: String,
+    pub action: String,
+    pub enabled: bool,
+}
+
+// as msg to get notification list
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryNotifications {
+    pub uname: String,
+    pub unread: bool, // unread only or all
+    pub page: i32,
+}
+
+impl Message for QueryNotifications {
+    type Result = Result<NotifyListMsg, ServiceError>;
+}
+
+// as msg to get unread count
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CountNotifications {
+    pub uname: String,
+}
+
+impl Message for CountNotifications {
+    type Result = Result<NotifyCountMsg, ServiceError>;
+}
+
+// as msg to mark read or unread
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReadNotification {
+    pub uname: String,
+    pub nid: String, // all as mark all
+    pub read: bool,
+}
+
+impl Message for ReadNotification {
+    type Result = Result<Msg, ServiceError>;
+}
+
+// as msg to get preference of user
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryNotifySettings {
+    pub uname: String,
+}
+
+impl Message for QueryNotifySettings {
+    type Result = Result<NotifySettingsMsg, ServiceError>;
+}
+
+// as msg to set preference per action
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UpdateNotifySetting {
+    pub uname: String,
+    pub action: String,
+    pub enabled: bool,
+}
+
+impl Message for UpdateNotifySetting {
+    type Result = Result<NotifySettingsMsg, ServiceError>;
+}
+
+impl Validate for UpdateNotifySetting {
+    fn validate(&self) -> Result<(), Error> {
+        let check = NOTIFY_ACTIONS.contains(&self.action.trim());
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
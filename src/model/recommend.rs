@@ -0,0 +1,46 @@
+// recommend typed model and msg handler
+
+use actix::Message;
+use diesel::sql_types::Float8;
+
+use crate::errors::ServiceError;
+use crate::model::item::Item;
+use crate::model::msg::{Msg, RecItemsMsg};
+
+// an item recommended, w/ the score of similarity or preference
+#[derive(Clone, Debug, Serialize, Deserialize, QueryableByName)]
+pub struct RecItem {
+    #[diesel(embed)]
+    pub item: Item,
+    #[sql_type = "Float8"]
+    pub score: f64,
+}
+
+// as msg to get items similar to an item
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QuerySimilarItems {
+    pub item_id: String,
+}
+
+impl Message for QuerySimilarItems {
+    type Result = Result<RecItemsMsg, ServiceError>;
+}
+
+// as msg to get recommendations for a user, + paging
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryRecommendations {
+    pub uname: String,
+    pub page: i32,
+}
+
+impl Message for QueryRecommendations {
+    type Result = Result<RecItemsMsg, ServiceError>;
+}
+
+// as msg to re-cal item similarity, sent by task
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RefreshItemSims;
+
+impl Message for RefreshItemSims {
+    type Result = Result<Msg, ServiceError>;
+}
@@ -3,14 +3,17 @@
 use actix::Message;
 use actix_web::{error, Error};
 use chrono::{NaiveDateTime, Utc};
+use diesel::sql_types::{BigInt, Float8};
 
 use crate::errors::ServiceError;
-use crate::model::msg::{Msg, RutListMsg, RutMsg, StarStatusMsg};
+use crate::model::msg::{Msg, RelatedRutsMsg, RutListMsg, RutMsg, StarStatusMsg};
 use crate::model::{re_test_url, test_len_limit, Validate, TITLE_LEN};
 use crate::schema::{ruts, starruts};
+use crate::util::md::md_to_html;
 
 // use to build select query
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[derive(QueryableByName)] // to load from raw sql, in search
 #[table_name = "ruts"]
 pub struct Rut {
     pub id: String,
@@ -28,6 +31,9 @@ pub struct Rut {
     pub star_count: i32,
     pub vote: i32, // cal per star, comment
     pub slug: String,
+    pub score: i32, // net of up/down votes
+    pub content_html: String,
+    pub visibility: String, // public|unlisted|private
 }
 
 // Rut's constructor
@@ -37,6 +43,7 @@ impl Rut {
             id: uid,
             title: rut.title,
             url: rut.url,
+            content_html: md_to_html(&rut.content),
             content: rut.content,
             create_at: Utc::now().naive_utc(),
             renew_at: Utc::now().naive_utc(),
@@ -49,8 +56,28 @@ impl Rut {
             star_count: 0,
             vote: 0,
             slug,
+            score: 0,
+            visibility: if rut.visibility.is_empty() {
+                "public".to_owned()
+            } else {
+                rut.visibility
+            },
         }
     }
+
+    // unlisted can be viewed via slug, private only by owner,
+    // and collaborators per db::collab::can_view
+    pub fn can_view(&self, viewer: &str) -> bool {
+        self.visibility != "private" || self.uname == viewer
+    }
+}
+
+// visibility of rut, public as default
+pub const VISIBILITIES: [&str; 3] = ["public", "unlisted", "private"];
+
+// empty as default or unchanged
+fn test_visibility(v: &str) -> bool {
+    v.is_empty() || VISIBILITIES.contains(&v)
 }
 
 // as msg in create new
@@ -62,6 +89,8 @@ pub struct CreateRut {
     pub author: String,
     pub uname: String,
     pub credential: String,
+    #[serde(default)]
+    pub visibility: String,
 }
 
 impl Message for CreateRut {
@@ -79,7 +108,7 @@ impl Validate for CreateRut {
         let check_len = test_len_limit(&self.title, 3, TITLE_LEN)
             && test_len_limit(&self.author, 0, 64)
             && test_len_limit(&self.credential, 0, 64);
-        let check = url_test && check_len;
+        let check = url_test && check_len && test_visibility(&self.visibility);
 
         if check {
             Ok(())
@@ -93,6 +122,7 @@ impl Validate for CreateRut {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct QueryRut {
     pub rut_slug: String,
+    pub uname: String, // who view, empty if anonymous
     // pub action: String, // get / delete, to do
 }
 
@@ -103,10 +133,10 @@ impl Message for QueryRut {
 // as msg to get  rut list, + paging
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum QueryRuts {
-    Index(String),
-    UserID(String, String, i32), // uname, create|star, paging
+    Index(String, String, i32), // mode: new|hot|top, window: week|month|all, paging
+    UserID(String, String, String, i32), // uname, create|star, viewer, paging
     ItemID(String, i32),
-    TagID(String, i32),
+    TagID(String, String, i32),         // tname, sort: count|score, paging
     KeyID(String, String, String, i32), // keyword, per, perid(uname|item|tname), paging
 }
 
@@ -114,6 +144,32 @@ impl Message for QueryRuts {
     type Result = Result<RutListMsg, ServiceError>;
 }
 
+// a rut related per shared items and tags, load from raw sql
+#[derive(Clone, Debug, Serialize, Deserialize, QueryableByName)]
+pub struct RelatedRut {
+    #[diesel(embed)]
+    pub rut: Rut,
+    #[sql_type = "BigInt"]
+    pub shared_items: i64,
+    #[sql_type = "BigInt"]
+    pub shared_tags: i64,
+    #[sql_type = "Float8"]
+    pub item_score: f64, // jaccard of item sets
+    #[sql_type = "Float8"]
+    pub score: f64, // weighted of item and tag jaccard
+}
+
+// as msg to get related ruts of a rut
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryRelatedRuts {
+    pub rut_id: String,
+    pub viewer: String, // per visibility of rut
+}
+
+impl Message for QueryRelatedRuts {
+    type Result = Result<RelatedRutsMsg, ServiceError>;
+}
+
 // as msg in update rut
 #[derive(Deserialize, Serialize, Debug, Clone, AsChangeset)]
 #[table_name = "ruts"]
@@ -124,6 +180,10 @@ pub struct UpdateRut {
     pub content: String,
     pub author: String,
     pub credential: String,
+    #[serde(default)]
+    pub visibility: String, // empty as unchanged
+    #[serde(default)]
+    pub uname: String, // to check permission
 }
 
 impl Message for UpdateRut {
@@ -141,7 +201,7 @@ impl Validate for UpdateRut {
         let check_len = test_len_limit(&self.title, 3, TITLE_LEN)
             && test_len_limit(&self.author, 0, 64)
             && test_len_limit(&self.credential, 0, 64);
-        let check = url_test && check_len;
+        let check = url_test && check_len && test_visibility(&self.visibility);
 
         if check {
             Ok(())
@@ -184,3 +244,45 @@ pub struct StarRutStatus {
 impl Message for StarRutStatus {
     type Result = Result<StarStatusMsg, ServiceError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{test_visibility, CreateRut, Rut};
+
+    fn rut_of(owner: &str, visibility: &str) -> Rut {
+        let cr = CreateRut {
+            title: "t".to_owned(),
+            url: "".to_owned(),
+            content: "".to_owned(),
+            author: "".to_owned(),
+            uname: owner.to_owned(),
+            credential: "".to_owned(),
+            visibility: visibility.to_owned(),
+        };
+        Rut::new("id".to_owned(), "slug".to_owned(), cr)
+    }
+
+    #[test]
+    fn valid_visibility() {
+        assert!(test_visibility(""));
+        assert!(test_visibility("public"));
+        assert!(test_visibility("unlisted"));
+        assert!(test_visibility("private"));
+        assert!(!test_visibility("secret"));
+        assert!(!test_visibility("Public"));
+    }
+
+    #[test]
+    fn public_as_default() {
+        assert_eq!(rut_of("owner", "").visibility, "public");
+    }
+
+    #[test]
+    fn view_per_visibility() {
+        assert!(rut_of("owner", "public").can_view(""));
+        assert!(rut_of("owner", "unlisted").can_view("other"));
+        assert!(!rut_of("owner", "private").can_view("other"));
+        assert!(!rut_of("owner", "private").can_view(""));
+        assert!(rut_of("owner", "private").can_view("owner"));
+    }
+}
@@ -0,0 +1,183 @@
+// full-text search typed model
+
+use actix::Message;
+use actix_web::{error, Error};
+use diesel::sql_types::{BigInt, Float4, Text};
+
+use crate::errors::ServiceError;
+use crate::model::etc::Etc;
+use crate::model::item::Item;
+use crate::model::msg::{Msg, SearchMsg};
+use crate::model::rut::Rut;
+use crate::model::{test_len_limit, Validate};
+use crate::util::feed::escape_xml;
+
+// text search config, must be same as the generated tsv column
+pub const TS_CONFIG: &str = "english";
+
+// sentinel of hit in ts_headline, private use char, replaced as <mark> after escape
+pub const HL_START: &str = "\u{E000}";
+pub const HL_STOP: &str = "\u{E001}";
+
+// trigram similarity threshold in fuzzy lookup of item
+pub const SIMILARITY: &str = "0.3";
+pub const WORD_SIMILARITY: &str = "0.5";
+// hits in fuzzy lookup of item
+pub const FUZZY_LIMIT: i64 = 10;
+
+// types can be searched, empty for all
+pub const SEARCH_TYPES: [&str; 5] = ["rut", "item", "tag", "user", "etc"];
+// hits per type when search all types, grouped
+pub const SEARCH_GROUP: i32 = 5;
+
+// filter of search, empty as no filter
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SearchScope {
+    pub tag: String,      // tname
+    pub user: String,     // uname, who create rut|etc, done item
+    pub item: String,     // item id, collected in rut|commented
+    pub category: String, // category of item
+}
+
+impl SearchScope {
+    // per keyword from user|tag|item, as in /ruts/key, /items/key
+    pub fn per(per: &str, perid: &str) -> Self {
+        let mut scope = SearchScope::default();
+        match per {
+            "user" => scope.user = perid.to_owned(),
+            "tag" => scope.tag = perid.to_owned(),
+            "item" => scope.item = perid.to_owned(),
+            _ => (),
+        }
+        scope
+    }
+}
+
+// as msg to search across ruts, items, tags, users, etcs
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Search {
+    pub q: String,
+    pub kind: String, // rut|item|tag|user|etc, empty for all
+    pub scope: SearchScope,
+    pub page: i32,
+}
+
+impl Message for Search {
+    type Result = Result<SearchMsg, ServiceError>;
+}
+
+// as msg to commit the pending changes of search index, by task
+pub struct CommitIndex;
+
+impl Message for CommitIndex {
+    type Result = Result<Msg, ServiceError>;
+}
+
+impl Validate for Search {
+    fn validate(&self) -> Result<(), Error> {
+        let check_kind = self.kind.is_empty() || SEARCH_TYPES.contains(&self.kind.as_str());
+        let check = check_kind && test_len_limit(&clean_keyword(&self.q), 1, 256);
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
+
+// a rut hit in search, load from raw sql
+#[derive(Clone, Debug, QueryableByName)]
+pub struct RutHit {
+    #[diesel(embed)]
+    pub rut: Rut,
+    #[sql_type = "Float4"]
+    pub rank: f32,
+    #[sql_type = "Text"]
+    pub headline: String,
+    #[sql_type = "BigInt"]
+    pub total: i64, // count(*) over()
+}
+
+// an item hit in search, load from raw sql
+#[derive(Clone, Debug, QueryableByName)]
+pub struct ItemHit {
+    #[diesel(embed)]
+    pub item: Item,
+    #[sql_type = "Float4"]
+    pub rank: f32,
+    #[sql_type = "Text"]
+    pub headline: String,
+    #[sql_type = "BigInt"]
+    pub total: i64,
+}
+
+// an etc hit in search, load from raw sql
+#[derive(Clone, Debug, QueryableByName)]
+pub struct EtcHit {
+    #[diesel(embed)]
+    pub etc: Etc,
+    #[sql_type = "Float4"]
+    pub rank: f32,
+    #[sql_type = "Text"]
+    pub headline: String,
+    #[sql_type = "BigInt"]
+    pub total: i64,
+}
+
+// an item in fuzzy lookup per trigram, load from raw sql
+#[derive(Clone, Debug, QueryableByName)]
+pub struct ItemSim {
+    #[diesel(embed)]
+    pub item: Item,
+    #[sql_type = "Float4"]
+    pub sim: f32,
+}
+
+// count of hits per type w/ tsv
+#[derive(Clone, Debug, QueryableByName)]
+pub struct HitCount {
+    #[sql_type = "BigInt"]
+    pub rut: i64,
+    #[sql_type = "BigInt"]
+    pub item: i64,
+    #[sql_type = "BigInt"]
+    pub etc: i64,
+}
+
+// keyword from client may be like %k%, as used in ilike before
+pub fn clean_keyword(k: &str) -> String {
+    k.replace('%', " ").trim().to_string()
+}
+
+// escape the raw headline, then mark the hits, safe to render as html
+pub fn mark_headline(raw: &str) -> String {
+    escape_xml(raw)
+        .replace(HL_START, "<mark>")
+        .replace(HL_STOP, "</mark>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clean_keyword, mark_headline, HL_START, HL_STOP};
+
+    #[test]
+    fn clean_wildcard() {
+        assert_eq!(clean_keyword(" rust%lang% "), "rust lang");
+        assert_eq!(clean_keyword("%%"), "");
+    }
+
+    #[test]
+    fn mark_hits() {
+        let raw = format!("learn {}rust{} fast", HL_START, HL_STOP);
+        assert_eq!(mark_headline(&raw), "learn <mark>rust</mark> fast");
+    }
+
+    #[test]
+    fn escape_before_mark() {
+        let raw = format!("<b>{}x</b>{}", HL_START, HL_STOP);
+        let h = mark_headline(&raw);
+        assert_eq!(h, "&lt;b&gt;<mark>x&lt;/b&gt;</mark>");
+        assert!(!h.contains("<b>"));
+    }
+}
@@ -0,0 +1,119 @@
+// suggest typed model and msg handler: propose an item to others' rut
+
+use actix::Message;
+use actix_web::{error, Error};
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+use crate::model::msg::{SuggestListMsg, SuggestMsg};
+use crate::model::{test_len_limit, Validate};
+use crate::schema::rutsuggests;
+
+// status of suggestion
+pub const SUGGEST_STATUS: [&str; 3] = ["pending", "accepted", "declined"];
+
+// use to build select query
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "rutsuggests"]
+pub struct RutSuggest {
+    pub id: String,
+    pub rut_id: String,
+    pub item_id: String,
+    pub uname: String, // who suggest
+    pub note: String,
+    pub status: String, // pending|accepted|declined
+    pub reason: String, // why declined
+    pub decide_by: String,
+    pub collect_id: String, // the collect once accepted
+    pub suggest_at: NaiveDateTime,
+    pub decide_at: Option<NaiveDateTime>,
+}
+
+// as msg to suggest an item to rut, by any user
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewSuggest {
+    #[serde(default)]
+    pub rut_id: String,
+    pub item_id: String,
+    pub note: String,
+    #[serde(default)]
+    pub uname: String,
+}
+
+impl Message for NewSuggest {
+    type Result = Result<SuggestMsg, ServiceError>;
+}
+
+impl Validate for NewSuggest {
+    fn validate(&self) -> Result<(), Error> {
+        let check = test_len_limit(&self.item_id, 1, 64) && test_len_limit(&self.note, 0, 512);
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
+
+// as msg to accept the suggestion as collect, by rut owner|editor
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AcceptSuggest {
+    pub suggest_id: String,
+    pub uname: String, // to check permission
+}
+
+impl Message for AcceptSuggest {
+    type Result = Result<SuggestMsg, ServiceError>;
+}
+
+// as msg to decline the suggestion w/ reason, by rut owner|editor
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DeclineSuggest {
+    #[serde(default)]
+    pub suggest_id: String,
+    pub reason: String,
+    #[serde(default)]
+    pub uname: String, // to check permission
+}
+
+impl Message for DeclineSuggest {
+    type Result = Result<SuggestMsg, ServiceError>;
+}
+
+impl Validate for DeclineSuggest {
+    fn validate(&self) -> Result<(), Error> {
+        let check = test_len_limit(&self.reason, 0, 512);
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
+
+// as msg to get the suggestions to a rut, for owner|editor, + paging
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QuerySuggests {
+    pub rut_id: String,
+    pub status: String, // pending|accepted|declined, empty for all
+    pub uname: String,  // to check permission
+    pub page: i32,
+}
+
+impl Message for QuerySuggests {
+    type Result = Result<SuggestListMsg, ServiceError>;
+}
+
+impl Validate for QuerySuggests {
+    fn validate(&self) -> Result<(), Error> {
+        let check = self.status.is_empty() || SUGGEST_STATUS.contains(&self.status.as_str());
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
@@ -5,9 +5,11 @@ use actix_web::{error, Error};
 use chrono::NaiveDateTime;
 
 use crate::errors::ServiceError;
-use crate::model::msg::{Msg, StarStatusMsg, TagListMsg, TagMsg};
+use crate::model::msg::{
+    Msg, RelatedTagsMsg, StarStatusMsg, SuggestTagsMsg, TagListMsg, TagMsg,
+};
 use crate::model::{re_test_url, test_len_limit, Validate, TAG_LEN};
-use crate::schema::{startags, tagetcs, tagitems, tagruts, tags};
+use crate::schema::{relatedtags, startags, tagetcs, tagitems, tagruts, tags, tagvotes};
 
 // use to build select query
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
@@ -23,6 +25,7 @@ pub struct Tag {
     pub etc_count: i32,
     pub star_count: i32,
     pub vote: i32, //cal per star,rut,item,comment
+    pub intro_html: String,
 }
 
 // Rut's constructor
@@ -39,6 +42,7 @@ impl Tag {
             etc_count: 0,
             star_count: 0,
             vote: 0,
+            intro_html: "".to_owned(),
         }
     }
 }
@@ -69,7 +73,7 @@ impl Validate for CheckTag {
 // as msg in query tag list
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum QueryTags {
-    RutID(String),
+    RutID(String, String), // id, viewer per visibility of rut
     ItemID(String),
     TagID(String),
     UserID(String),
@@ -120,7 +124,8 @@ pub struct TagRut {
     pub id: String,
     pub tname: String,
     pub rut_id: String,
-    pub count: i32,
+    pub count: i32,   // num of user votes
+    pub locked: bool, // locked by rut owner, keep even no votes
 }
 
 // as msg in tag or untag rut
@@ -129,6 +134,8 @@ pub struct RutTag {
     pub tnames: Vec<String>,
     pub rut_id: String,
     pub action: u8, // tag 1 or untag 0
+    #[serde(default)]
+    pub uname: String, // who tag, per auth
 }
 
 impl Message for RutTag {
@@ -209,6 +216,8 @@ pub struct TagAny {
     pub tag_to: String, // rut|item|etc
     pub to_id: String,
     pub action: u8, // tag 1 or untag 0
+    #[serde(default)]
+    pub uname: String, // who tag, per auth
 }
 
 impl Message for TagAny {
@@ -231,3 +240,88 @@ impl Validate for TagAny {
         }
     }
 }
+
+// record who tag a rut|item, one vote per user per tag per target
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "tagvotes"]
+pub struct TagVote {
+    pub id: String,
+    pub uname: String,
+    pub tname: String,
+    pub vote_to: String, // rut|item
+    pub to_id: String,
+    pub vote_at: NaiveDateTime,
+}
+
+// as msg to get the tags voted by a user on a rut|item
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryTagVotes {
+    pub uname: String,
+    pub vote_to: String,
+    pub to_id: String,
+}
+
+impl Message for QueryTagVotes {
+    type Result = Result<TagListMsg, ServiceError>;
+}
+
+// as msg in rut owner lock|unlock|remove a tag on rut
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ModerateRutTag {
+    pub rut_id: String,
+    pub tname: String,
+    pub action: String, // lock|unlock|remove
+    pub uname: String,  // to check permission
+}
+
+impl Message for ModerateRutTag {
+    type Result = Result<Msg, ServiceError>;
+}
+
+// related tag per co-occurrence on a same rut or item
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "relatedtags"]
+pub struct RelatedTag {
+    pub id: String,
+    pub tname: String,
+    pub related: String,
+    pub co_count: i32, // times tagged on a same rut or item
+    pub score: f64,    // co_count normalized per popularity of both tags
+    pub cal_at: NaiveDateTime,
+}
+
+// as msg to get related tags of a tag
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryRelatedTags {
+    pub tname: String,
+}
+
+impl Message for QueryRelatedTags {
+    type Result = Result<RelatedTagsMsg, ServiceError>;
+}
+
+// as msg to re-cal related tags, sent by task
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RefreshRelatedTags;
+
+impl Message for RefreshRelatedTags {
+    type Result = Result<Msg, ServiceError>;
+}
+
+// tag suggested to an item or rut, with a confidence in 0..1
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SuggestedTag {
+    pub tname: String,
+    pub confidence: f64,
+}
+
+// as msg to get suggested tags of an item or rut
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub enum SuggestTags {
+    ItemID(String),
+    RutID(String),
+}
+
+impl Message for SuggestTags {
+    type Result = Result<SuggestTagsMsg, ServiceError>;
+}
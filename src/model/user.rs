@@ -23,6 +23,7 @@ pub struct User {
     pub intro: String,
     pub location: String,
     pub nickname: String,
+    pub intro_html: String,
 }
 
 // User's constructor
@@ -38,6 +39,7 @@ impl User {
             intro: "".to_owned(),
             location: "".to_owned(),
             nickname: "".to_owned(),
+            intro_html: "".to_owned(),
         }
     }
 }
@@ -54,6 +56,7 @@ pub struct CheckUser {
     pub intro: String,
     pub location: String,
     pub nickname: String,
+    pub intro_html: String,
 }
 
 impl From<User> for CheckUser {
@@ -67,6 +70,7 @@ impl From<User> for CheckUser {
             intro: user.intro,
             location: user.location,
             nickname: user.nickname,
+            intro_html: user.intro_html,
         }
     }
 }
@@ -128,6 +132,7 @@ impl From<Claims> for CheckUser {
             intro: "".to_owned(),
             location: "".to_owned(),
             nickname: "".to_owned(),
+            intro_html: "".to_owned(),
         }
     }
 }
@@ -258,9 +263,8 @@ impl Validate for ChangePsw {
     }
 }
 
-// to do:
 // User follow
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
 #[table_name = "follows"]
 pub struct Follow {
     pub id: String,
@@ -270,6 +274,18 @@ pub struct Follow {
     pub note: String,
 }
 
+// as msg to follow or unfollow user
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct FollowUser {
+    pub uname: String,
+    pub fname: String,
+    pub action: u8, // 0- unfollow, 1- follow
+}
+
+impl Message for FollowUser {
+    type Result = Result<Msg, ServiceError>;
+}
+
 // user's activity record
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable)]
 #[table_name = "timelines"]
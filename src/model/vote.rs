@@ -0,0 +1,61 @@
+// vote typed model and msg handler
+
+use actix::Message;
+use actix_web::{error, Error};
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+use crate::model::msg::VoteMsg;
+use crate::model::Validate;
+use crate::schema::votes;
+
+// up or down vote on etc|rut|item
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "votes"]
+pub struct Vote {
+    pub id: String,
+    pub uname: String,
+    pub vote_to: String, // etc|rut|item
+    pub to_id: String,
+    pub updown: i16, // 1- up, -1- down
+    pub vote_at: NaiveDateTime,
+}
+
+// as msg in vote up|down or retract
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VoteFor {
+    pub uname: String,
+    pub vote_to: String,
+    pub to_id: String,
+    pub updown: i16, // 1- up, -1- down, 0- retract
+}
+
+impl Message for VoteFor {
+    type Result = Result<VoteMsg, ServiceError>;
+}
+
+impl Validate for VoteFor {
+    fn validate(&self) -> Result<(), Error> {
+        let to = self.vote_to.trim();
+        let check = (to == "etc" || to == "rut" || to == "item")
+            && (self.updown >= -1 && self.updown <= 1);
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
+
+// as msg to check the vote of a user
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VoteStatus {
+    pub uname: String,
+    pub vote_to: String,
+    pub to_id: String,
+}
+
+impl Message for VoteStatus {
+    type Result = Result<VoteMsg, ServiceError>;
+}
@@ -0,0 +1,132 @@
+// webhook typed model and msg handler
+
+use actix::Message;
+use actix_web::{error, Error};
+use chrono::NaiveDateTime;
+
+use crate::errors::ServiceError;
+use crate::model::msg::{DeliveryListMsg, DeliveryMsg, Msg, WebhookListMsg, WebhookMsg};
+use crate::model::{re_test_url, Validate, URL_LEN};
+use crate::schema::{deliveries, webhooks};
+
+// events to subscribe
+pub const WEBHOOK_EVENTS: [&str; 5] = [
+    "rut_created",
+    "rut_updated",
+    "item_collected",
+    "etc_posted",
+    "item_done",
+];
+
+// use to build select query
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[table_name = "webhooks"]
+pub struct Webhook {
+    pub id: String,
+    pub uname: String,
+    pub url: String,
+    pub secret: String, // to sign payload, hmac-sha256
+    pub events: Vec<String>,
+    pub active: bool,
+    pub create_at: NaiveDateTime,
+}
+
+// delivery log, as outbox to deliver and retry
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Identifiable, Queryable, Insertable)]
+#[derive(QueryableByName)] // to load from raw sql, in claim
+#[table_name = "deliveries"]
+pub struct Delivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    pub payload: String,
+    pub status: String, // pending|sending|success|failed
+    pub attempts: i32,
+    pub response_code: Option<i32>,
+    pub response_body: String,
+    pub create_at: NaiveDateTime,
+    pub next_at: NaiveDateTime, // next attempt
+    pub deliver_at: Option<NaiveDateTime>,
+}
+
+// as msg in register webhook
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewWebhook {
+    pub uname: String,
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+impl Message for NewWebhook {
+    type Result = Result<WebhookMsg, ServiceError>;
+}
+
+impl Validate for NewWebhook {
+    fn validate(&self) -> Result<(), Error> {
+        let url = self.url.trim();
+        let check_url = re_test_url(url) && url.len() <= URL_LEN;
+        let check_events = self.events.len() > 0
+            && self
+                .events
+                .iter()
+                .all(|e| WEBHOOK_EVENTS.contains(&e.trim()));
+        let check = check_url && check_events;
+
+        if check {
+            Ok(())
+        } else {
+            Err(error::ErrorBadRequest("Invalid Input"))
+        }
+    }
+}
+
+// as msg to get webhooks of user
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryWebhooks {
+    pub uname: String,
+}
+
+impl Message for QueryWebhooks {
+    type Result = Result<WebhookListMsg, ServiceError>;
+}
+
+// as msg to del webhook
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DelWebhook {
+    pub webhook_id: String,
+    pub uname: String, // to check permission
+}
+
+impl Message for DelWebhook {
+    type Result = Result<Msg, ServiceError>;
+}
+
+// as msg to get delivery log of webhook
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryDeliveries {
+    pub webhook_id: String,
+    pub uname: String, // to check permission
+    pub page: i32,
+}
+
+impl Message for QueryDeliveries {
+    type Result = Result<DeliveryListMsg, ServiceError>;
+}
+
+// as msg to deliver a ping at once, to task::deliver
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TestWebhook {
+    pub webhook_id: String,
+    pub uname: String, // to check permission
+}
+
+impl Message for TestWebhook {
+    type Result = Result<DeliveryMsg, ServiceError>;
+}
+
+// as msg to deliver the pending, to task::deliver
+pub struct FlushDeliveries;
+
+impl Message for FlushDeliveries {
+    type Result = Result<Msg, ServiceError>;
+}
@@ -7,6 +7,32 @@ table! {
         content -> Text,
         uname -> Varchar,
         collect_at -> Timestamp,
+        content_html -> Text,
+    }
+}
+
+table! {
+    deliveries (id) {
+        id -> Varchar,
+        webhook_id -> Varchar,
+        event -> Varchar,
+        payload -> Text,
+        status -> Varchar,
+        attempts -> Int4,
+        response_code -> Nullable<Int4>,
+        response_body -> Text,
+        create_at -> Timestamp,
+        next_at -> Timestamp,
+        deliver_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    digestsettings (id) {
+        id -> Varchar,
+        uname -> Varchar,
+        frequency -> Varchar,
+        last_sent -> Nullable<Timestamp>,
     }
 }
 
@@ -21,6 +47,15 @@ table! {
         tname -> Varchar,
         uname -> Varchar,
         vote -> Int4,
+        reply_count -> Int4,
+        edited_at -> Nullable<Timestamp>,
+        deleted -> Bool,
+        kind -> Varchar,
+        page -> Nullable<Int4>,
+        chapter -> Varchar,
+        location -> Varchar,
+        percent -> Nullable<Float4>,
+        content_html -> Text,
     }
 }
 
@@ -52,6 +87,78 @@ table! {
         done_count -> Int4,
         vote -> Int4,
         slug -> Varchar,
+        score -> Int4,
+    }
+}
+
+table! {
+    itemsims (id) {
+        id -> Varchar,
+        item_id -> Varchar,
+        similar_id -> Varchar,
+        co_count -> Int4,
+        score -> Float8,
+        cal_at -> Timestamp,
+    }
+}
+
+table! {
+    mentions (id) {
+        id -> Varchar,
+        uname -> Varchar,
+        by_uname -> Varchar,
+        mention_to -> Varchar,
+        to_id -> Varchar,
+        rut_id -> Varchar,
+        item_id -> Varchar,
+        mention_at -> Timestamp,
+    }
+}
+
+table! {
+    notifications (id) {
+        id -> Varchar,
+        uname -> Varchar,
+        actor -> Varchar,
+        action -> Varchar,
+        obj -> Varchar,
+        objid -> Varchar,
+        content -> Varchar,
+        is_read -> Bool,
+        notify_at -> Timestamp,
+    }
+}
+
+table! {
+    notifysettings (id) {
+        id -> Varchar,
+        uname -> Varchar,
+        action -> Varchar,
+        enabled -> Bool,
+    }
+}
+
+table! {
+    relatedtags (id) {
+        id -> Varchar,
+        tname -> Varchar,
+        related -> Varchar,
+        co_count -> Int4,
+        score -> Float8,
+        cal_at -> Timestamp,
+    }
+}
+
+table! {
+    rutcollabs (id) {
+        id -> Varchar,
+        rut_id -> Varchar,
+        uname -> Varchar,
+        role -> Varchar,
+        accepted -> Bool,
+        invite_by -> Varchar,
+        invite_at -> Timestamp,
+        accept_at -> Nullable<Timestamp>,
     }
 }
 
@@ -72,6 +179,25 @@ table! {
         star_count -> Int4,
         vote -> Int4,
         slug -> Varchar,
+        score -> Int4,
+        content_html -> Text,
+        visibility -> Varchar,
+    }
+}
+
+table! {
+    rutsuggests (id) {
+        id -> Varchar,
+        rut_id -> Varchar,
+        item_id -> Varchar,
+        uname -> Varchar,
+        note -> Text,
+        status -> Varchar,
+        reason -> Text,
+        decide_by -> Varchar,
+        collect_id -> Varchar,
+        suggest_at -> Timestamp,
+        decide_at -> Nullable<Timestamp>,
     }
 }
 
@@ -130,6 +256,7 @@ table! {
         tname -> Varchar,
         rut_id -> Varchar,
         count -> Int4,
+        locked -> Bool,
     }
 }
 
@@ -145,6 +272,18 @@ table! {
         etc_count -> Int4,
         star_count -> Int4,
         vote -> Int4,
+        intro_html -> Text,
+    }
+}
+
+table! {
+    tagvotes (id) {
+        id -> Varchar,
+        uname -> Varchar,
+        tname -> Varchar,
+        vote_to -> Varchar,
+        to_id -> Varchar,
+        vote_at -> Timestamp,
     }
 }
 
@@ -170,10 +309,35 @@ table! {
         intro -> Text,
         location -> Varchar,
         nickname -> Varchar,
+        intro_html -> Text,
+    }
+}
+
+table! {
+    votes (id) {
+        id -> Varchar,
+        uname -> Varchar,
+        vote_to -> Varchar,
+        to_id -> Varchar,
+        updown -> Int2,
+        vote_at -> Timestamp,
+    }
+}
+
+table! {
+    webhooks (id) {
+        id -> Varchar,
+        uname -> Varchar,
+        url -> Varchar,
+        secret -> Varchar,
+        events -> Array<Text>,
+        active -> Bool,
+        create_at -> Timestamp,
     }
 }
 
 allow_tables_to_appear_in_same_query!(
-    collects, etcs, follows, items, ruts, staritems, starruts, startags, tagetcs, tagitems,
-    tagruts, tags, timelines, users,
+    collects, deliveries, digestsettings, etcs, follows, items, itemsims, mentions, notifications,
+    notifysettings, relatedtags, rutcollabs, ruts, rutsuggests, staritems, starruts, startags,
+    tagetcs, tagitems, tagruts, tags, tagvotes, timelines, users, votes, webhooks,
 );
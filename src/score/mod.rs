@@ -0,0 +1,192 @@
+// score mod
+
+// popularity of rut, item, tag, in one place:
+// vote: weighted sum of the counters, re-cal once a counter changed, and per task
+// hotness and windowed top of rut, per the recent events: star, collect, comment
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::errors::ServiceError;
+
+// weight per event|counter, configurable per env
+pub struct Weights {
+    pub star: i32,    // star a rut|tag
+    pub collect: i32, // collect an item into rut, tag a rut|item
+    pub comment: i32, // comment on rut|item, tag an etc
+    pub done: i32,    // done an item
+}
+
+lazy_static! {
+    pub static ref WEIGHTS: Weights = Weights {
+        star: get_weight("SCORE_STAR", 1),
+        collect: get_weight("SCORE_COLLECT", 2),
+        comment: get_weight("SCORE_COMMENT", 1),
+        done: get_weight("SCORE_DONE", 1),
+    };
+}
+
+// get weight from env or use default
+fn get_weight(key: &str, default: i32) -> i32 {
+    dotenv::var(key)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default)
+}
+
+// re-cal vote of a rut per current counters, call after a counter changed
+pub fn vote_rut(conn: &PgConnection, rutid: &str) -> Result<(), ServiceError> {
+    use crate::schema::ruts::dsl::*;
+    let w = &*WEIGHTS;
+    diesel::update(ruts.filter(&id.eq(rutid)))
+        .set(vote.eq(item_count * w.collect + comment_count * w.comment + star_count * w.star))
+        .execute(conn)?;
+    Ok(())
+}
+
+// re-cal vote of an item per current counters, call after a counter changed
+pub fn vote_item(conn: &PgConnection, itemid: &str) -> Result<(), ServiceError> {
+    use crate::schema::items::dsl::*;
+    let w = &*WEIGHTS;
+    diesel::update(items.filter(&id.eq(itemid)))
+        .set(vote.eq(rut_count * w.collect + etc_count * w.comment + done_count * w.done))
+        .execute(conn)?;
+    Ok(())
+}
+
+// re-cal vote of tags per current counters, call after a counter changed
+pub fn vote_tags(conn: &PgConnection, tnames: &[String]) -> Result<(), ServiceError> {
+    use crate::schema::tags::dsl::*;
+    use diesel::dsl::any;
+    let w = &*WEIGHTS;
+    diesel::update(tags.filter(&tname.eq(any(tnames))))
+        .set(vote.eq(
+            (rut_count + item_count) * w.collect + etc_count * w.comment + star_count * w.star,
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn vote_tag(conn: &PgConnection, tg: &str) -> Result<(), ServiceError> {
+    vote_tags(conn, &[tg.to_owned()])
+}
+
+// re-cal all votes, once weights changed or counters drifted, return the num of updated
+pub fn recompute_votes(conn: &PgConnection) -> Result<usize, ServiceError> {
+    let w = &*WEIGHTS;
+    let mut num = 0;
+    {
+        use crate::schema::ruts::dsl::*;
+        let cal = item_count * w.collect + comment_count * w.comment + star_count * w.star;
+        num += diesel::update(ruts.filter(vote.ne(cal)))
+            .set(vote.eq(cal))
+            .execute(conn)?;
+    }
+    {
+        use crate::schema::items::dsl::*;
+        let cal = rut_count * w.collect + etc_count * w.comment + done_count * w.done;
+        num += diesel::update(items.filter(vote.ne(cal)))
+            .set(vote.eq(cal))
+            .execute(conn)?;
+    }
+    {
+        use crate::schema::tags::dsl::*;
+        let cal =
+            (rut_count + item_count) * w.collect + etc_count * w.comment + star_count * w.star;
+        num += diesel::update(tags.filter(vote.ne(cal)))
+            .set(vote.eq(cal))
+            .execute(conn)?;
+    }
+    Ok(num)
+}
+
+// an event loses half of the weight per the hours
+pub const HALF_LIFE_HOURS: f64 = 24.0;
+// only the events in recent days count in hotness
+pub const HOT_DAYS: i32 = 7;
+
+// modes of index
+pub const INDEX_MODES: [&str; 3] = ["new", "hot", "top"];
+
+// days per window of top, None for all
+pub fn window_days(window: &str) -> Option<i32> {
+    match window {
+        "month" => Some(30),
+        "all" => None,
+        _ => Some(7), // week as default
+    }
+}
+
+// events on rut in recent days, the timestamp stored as utc
+fn events_sql(days: i32) -> String {
+    format!(
+        "
+        SELECT rut_id, star_at AS at, {sw} AS w FROM starruts
+        WHERE star_at > (NOW() AT TIME ZONE 'UTC') - INTERVAL '{d} days'
+        UNION ALL
+        SELECT rut_id, collect_at AS at, {cw} AS w FROM collects
+        WHERE collect_at > (NOW() AT TIME ZONE 'UTC') - INTERVAL '{d} days'
+        UNION ALL
+        SELECT rut_id, post_at AS at, {ew} AS w FROM etcs
+        WHERE rut_id != '' AND NOT deleted
+            AND post_at > (NOW() AT TIME ZONE 'UTC') - INTERVAL '{d} days'
+",
+        sw = WEIGHTS.star,
+        cw = WEIGHTS.collect,
+        ew = WEIGHTS.comment,
+        d = days,
+    )
+}
+
+// hot = sum(w * 0.5 ^ (age_hours / half_life)), params: $1 limit, $2 offset
+pub fn hot_sql() -> String {
+    format!(
+        "
+    SELECT r.* FROM ruts r LEFT JOIN (
+        SELECT rut_id, SUM(w * POWER(0.5,
+            EXTRACT(EPOCH FROM (NOW() AT TIME ZONE 'UTC') - at) / 3600 / {hl}
+        ))::FLOAT8 AS hot
+        FROM ({ev}) ev GROUP BY rut_id
+    ) h ON h.rut_id = r.id
+    WHERE r.visibility = 'public'
+    ORDER BY COALESCE(h.hot, 0) DESC, r.renew_at DESC
+    LIMIT $1 OFFSET $2
+",
+        hl = HALF_LIFE_HOURS,
+        ev = events_sql(HOT_DAYS),
+    )
+}
+
+// top = sum(w) in the window, no decay, params: $1 limit, $2 offset
+pub fn top_sql(days: i32) -> String {
+    format!(
+        "
+    SELECT r.* FROM ruts r LEFT JOIN (
+        SELECT rut_id, SUM(w) AS top FROM ({ev}) ev GROUP BY rut_id
+    ) t ON t.rut_id = r.id
+    WHERE r.visibility = 'public'
+    ORDER BY COALESCE(t.top, 0) DESC, r.vote DESC, r.renew_at DESC
+    LIMIT $1 OFFSET $2
+",
+        ev = events_sql(days),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::window_days;
+
+    #[test]
+    fn days_per_window() {
+        assert_eq!(window_days("week"), Some(7));
+        assert_eq!(window_days("month"), Some(30));
+        assert_eq!(window_days("all"), None);
+    }
+
+    #[test]
+    fn week_as_default() {
+        assert_eq!(window_days(""), Some(7));
+        assert_eq!(window_days("year"), Some(7));
+    }
+}
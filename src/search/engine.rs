@@ -0,0 +1,451 @@
+// search backend per tantivy on-disk index: stemming, boost per field, facet per tag|category
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{dsl::any, ExpressionMethods, QueryDsl, RunQueryDsl};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tantivy::collector::{Count, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{
+    Facet, Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED, STRING,
+};
+use tantivy::{Document, Index, IndexReader, IndexWriter, ReloadPolicy, SnippetGenerator, Term};
+
+use crate::errors::ServiceError;
+use crate::model::etc::Etc;
+use crate::model::item::Item;
+use crate::model::rut::Rut;
+use crate::model::search::{clean_keyword, HitCount, SearchScope};
+use crate::schema::{collects, etcs, items, ruts, staritems, tagetcs, tagitems, tagruts};
+use crate::search::{Doc, Hits, SearchBackend};
+
+// memory budget of index writer
+const WRITER_HEAP: usize = 50_000_000;
+// rows per batch in reindex
+const REINDEX_BATCH: i64 = 500;
+
+struct Fields {
+    kind: Field, // rut|item|etc
+    id: Field,
+    title: Field,
+    body: Field,    // content, detail
+    authors: Field, // author, authors, publisher, uiid
+    uname: Field,   // who create rut|etc, done item
+    item: Field,    // collected in rut, commented
+    tag: Field,
+    category: Field,
+}
+
+pub struct TantivyBackend {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    dirty: AtomicBool, // changed since last commit
+    f: Fields,
+}
+
+fn index_err<E: Display>(e: E) -> ServiceError {
+    warn!("search index: {}", e);
+    ServiceError::InternalServerError("search index".into())
+}
+
+impl TantivyBackend {
+    pub fn open(dir: &str) -> Result<Self, ServiceError> {
+        let stem = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("en_stem")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let mut sb = Schema::builder();
+        let f = Fields {
+            kind: sb.add_text_field("kind", STRING),
+            id: sb.add_text_field("id", STRING | STORED),
+            title: sb.add_text_field("title", stem.clone().set_stored()),
+            body: sb.add_text_field("body", stem.clone().set_stored()),
+            authors: sb.add_text_field("authors", stem),
+            uname: sb.add_text_field("uname", STRING),
+            item: sb.add_text_field("item", STRING),
+            tag: sb.add_facet_field("tag"),
+            category: sb.add_facet_field("category"),
+        };
+
+        std::fs::create_dir_all(dir).map_err(index_err)?;
+        let mmap = MmapDirectory::open(dir).map_err(index_err)?;
+        let index = Index::open_or_create(mmap, sb.build()).map_err(index_err)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommit)
+            .try_into()
+            .map_err(index_err)?;
+        let writer = index.writer(WRITER_HEAP).map_err(index_err)?;
+
+        Ok(TantivyBackend {
+            index,
+            reader,
+            writer: Mutex::new(writer),
+            dirty: AtomicBool::new(false),
+            f,
+        })
+    }
+
+    // keyword in title, body, authors, and filter per kind, scope
+    fn build_query(
+        &self,
+        kind: &str,
+        kw: &str,
+        scope: &SearchScope,
+    ) -> Result<BooleanQuery, ServiceError> {
+        let f = &self.f;
+        let mut qp = QueryParser::for_index(&self.index, vec![f.title, f.body, f.authors]);
+        qp.set_field_boost(f.title, 3.0);
+        qp.set_field_boost(f.authors, 1.5);
+        let text_q = qp
+            .parse_query(&clean_keyword(kw))
+            .map_err(|_| ServiceError::BadRequest("Invalid Query".into()))?;
+
+        let term_q = |field: Field, v: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, v),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let facet_q = |field: Field, v: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_facet(field, &Facet::from_path(vec![v])),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let mut clauses = vec![(Occur::Must, text_q), (Occur::Must, term_q(f.kind, kind))];
+        if !scope.tag.is_empty() {
+            clauses.push((Occur::Must, facet_q(f.tag, &scope.tag)));
+        }
+        if !scope.user.is_empty() {
+            clauses.push((Occur::Must, term_q(f.uname, &scope.user)));
+        }
+        if !scope.item.is_empty() {
+            clauses.push((Occur::Must, term_q(f.item, &scope.item)));
+        }
+        if !scope.category.is_empty() {
+            clauses.push((Occur::Must, facet_q(f.category, &scope.category)));
+        }
+
+        Ok(BooleanQuery::from(clauses))
+    }
+
+    // ids in rank, headlines per id, total
+    fn search_ids(
+        &self,
+        kind: &str,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<String>, ServiceError> {
+        let query = self.build_query(kind, kw, scope)?;
+        let searcher = self.reader.searcher();
+        if limit < 1 {
+            let total = searcher.search(&query, &Count).map_err(index_err)?;
+            return Ok((Vec::new(), HashMap::new(), total as i64));
+        }
+
+        let top = TopDocs::with_limit((limit + offset) as usize);
+        let (docs, total) = searcher.search(&query, &(top, Count)).map_err(index_err)?;
+        let snippets =
+            SnippetGenerator::create(&searcher, &query, self.f.body).map_err(index_err)?;
+
+        let mut ids = Vec::new();
+        let mut headlines = HashMap::new();
+        for (_score, addr) in docs.into_iter().skip(offset as usize) {
+            let doc = searcher.doc(addr).map_err(index_err)?;
+            let doc_id = match doc.get_first(self.f.id).and_then(|v| v.text()) {
+                Some(i) => i.to_owned(),
+                None => continue,
+            };
+            // html escaped, hits in <b>
+            let headline = snippets
+                .snippet_from_doc(&doc)
+                .to_html()
+                .replace("<b>", "<mark>")
+                .replace("</b>", "</mark>");
+            headlines.insert(doc_id.clone(), headline);
+            ids.push(doc_id);
+        }
+
+        Ok((ids, headlines, total as i64))
+    }
+
+    // build doc w/ the relations, None if not to be indexed
+    fn build_doc(
+        &self,
+        conn: &PgConnection,
+        doc: &Doc,
+    ) -> Result<Option<Document>, ServiceError> {
+        let f = &self.f;
+        let mut d = Document::default();
+        let mut tags: Vec<String> = Vec::new();
+        match doc {
+            Doc::Rut(r) => {
+                // only public rut can be searched
+                if r.visibility != "public" {
+                    return Ok(None);
+                }
+                d.add_text(f.kind, "rut");
+                d.add_text(f.id, &r.id);
+                d.add_text(f.title, &r.title);
+                d.add_text(f.body, &r.content);
+                d.add_text(f.authors, &r.author);
+                d.add_text(f.uname, &r.uname);
+                let item_ids = collects::table
+                    .filter(collects::rut_id.eq(&r.id))
+                    .select(collects::item_id)
+                    .load::<String>(conn)?;
+                for i in item_ids.iter() {
+                    d.add_text(f.item, i);
+                }
+                tags = tagruts::table
+                    .filter(tagruts::rut_id.eq(&r.id))
+                    .select(tagruts::tname)
+                    .load::<String>(conn)?;
+            }
+            Doc::Item(i) => {
+                d.add_text(f.kind, "item");
+                d.add_text(f.id, &i.id);
+                d.add_text(f.title, &i.title);
+                d.add_text(f.body, &i.detail);
+                d.add_text(f.authors, &format!("{} {} {}", i.authors, i.publisher, i.uiid));
+                if i.category.trim() != "" {
+                    d.add_facet(f.category, Facet::from_path(vec![i.category.trim()]));
+                }
+                let done_by = staritems::table
+                    .filter(staritems::item_id.eq(&i.id))
+                    .filter(staritems::flag.eq(3))
+                    .select(staritems::uname)
+                    .load::<String>(conn)?;
+                for u in done_by.iter() {
+                    d.add_text(f.uname, u);
+                }
+                tags = tagitems::table
+                    .filter(tagitems::item_id.eq(&i.id))
+                    .select(tagitems::tname)
+                    .load::<String>(conn)?;
+            }
+            Doc::Etc(e) => {
+                if e.deleted {
+                    return Ok(None);
+                }
+                // nor the comments on the rut not public
+                if e.rut_id != "" {
+                    let listed: i64 = ruts::table
+                        .filter(ruts::id.eq(&e.rut_id))
+                        .filter(ruts::visibility.eq("public"))
+                        .count()
+                        .get_result(conn)?;
+                    if listed == 0 {
+                        return Ok(None);
+                    }
+                }
+                d.add_text(f.kind, "etc");
+                d.add_text(f.id, &e.id);
+                d.add_text(f.body, &e.content);
+                d.add_text(f.uname, &e.uname);
+                d.add_text(f.item, &e.item_id);
+                tags = tagetcs::table
+                    .filter(tagetcs::etc_id.eq(&e.id))
+                    .select(tagetcs::tname)
+                    .load::<String>(conn)?;
+                tags.push(e.tname.clone());
+            }
+        }
+        for t in tags.iter().filter(|t| t.trim() != "") {
+            d.add_facet(f.tag, Facet::from_path(vec![t.trim()]));
+        }
+
+        Ok(Some(d))
+    }
+}
+
+// rows loaded per ids, then in the order of ids
+fn in_order<T, F: Fn(&T) -> String>(rows: Vec<T>, ids: &[String], key: F) -> Vec<T> {
+    let mut row_map: HashMap<String, T> = rows.into_iter().map(|r| (key(&r), r)).collect();
+    ids.iter().filter_map(|i| row_map.remove(i)).collect()
+}
+
+impl SearchBackend for TantivyBackend {
+    fn search_ruts(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Rut>, ServiceError> {
+        let (ids, headlines, total) = self.search_ids("rut", kw, scope, limit, offset)?;
+        let rows = ruts::table
+            .filter(ruts::id.eq(any(&ids)))
+            .load::<Rut>(conn)?;
+
+        Ok((in_order(rows, &ids, |r| r.id.clone()), headlines, total))
+    }
+
+    fn search_items(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Item>, ServiceError> {
+        let (ids, headlines, total) = self.search_ids("item", kw, scope, limit, offset)?;
+        let rows = items::table
+            .filter(items::id.eq(any(&ids)))
+            .load::<Item>(conn)?;
+
+        Ok((in_order(rows, &ids, |i| i.id.clone()), headlines, total))
+    }
+
+    fn search_etcs(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Etc>, ServiceError> {
+        let (ids, headlines, total) = self.search_ids("etc", kw, scope, limit, offset)?;
+        // the index may lag behind a rut gone private
+        let listed = ruts::table
+            .filter(ruts::visibility.eq("public"))
+            .select(ruts::id);
+        let rows = etcs::table
+            .filter(etcs::id.eq(any(&ids)))
+            .filter(etcs::rut_id.eq("").or(etcs::rut_id.eq_any(listed)))
+            .load::<Etc>(conn)?;
+
+        Ok((in_order(rows, &ids, |e| e.id.clone()), headlines, total))
+    }
+
+    fn count(
+        &self,
+        _conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+    ) -> Result<HitCount, ServiceError> {
+        Ok(HitCount {
+            rut: self.search_ids("rut", kw, scope, 0, 0)?.2,
+            item: self.search_ids("item", kw, scope, 0, 0)?.2,
+            etc: self.search_ids("etc", kw, scope, 0, 0)?.2,
+        })
+    }
+
+    // not commit per change, but in batch per task, as commit is costly
+    fn index(&self, conn: &PgConnection, doc: &Doc) -> Result<(), ServiceError> {
+        let doc_id = match doc {
+            Doc::Rut(r) => &r.id,
+            Doc::Item(i) => &i.id,
+            Doc::Etc(e) => &e.id,
+        };
+        let new_doc = self.build_doc(conn, doc)?;
+
+        let mut writer = self.writer.lock().map_err(index_err)?;
+        writer.delete_term(Term::from_field_text(self.f.id, doc_id));
+        if let Some(d) = new_doc {
+            writer.add_document(d);
+        }
+        self.dirty.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn remove(&self, doc_id: &str) -> Result<(), ServiceError> {
+        let mut writer = self.writer.lock().map_err(index_err)?;
+        writer.delete_term(Term::from_field_text(self.f.id, doc_id));
+        self.dirty.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn commit(&self) -> Result<(), ServiceError> {
+        if !self.dirty.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let mut writer = self.writer.lock().map_err(index_err)?;
+        // clear before commit, so the change during commit will be in next
+        self.dirty.store(false, Ordering::SeqCst);
+        if let Err(e) = writer.commit() {
+            self.dirty.store(true, Ordering::SeqCst);
+            return Err(index_err(e));
+        }
+        Ok(())
+    }
+
+    fn synced(&self) -> bool {
+        false
+    }
+
+    fn reindex(&self, conn: &PgConnection) -> Result<usize, ServiceError> {
+        let mut writer = self.writer.lock().map_err(index_err)?;
+        writer.delete_all_documents().map_err(index_err)?;
+        let mut doc_count = 0;
+
+        // page by id, per table
+        let mut last_id = String::new();
+        loop {
+            let rows = ruts::table
+                .filter(ruts::id.gt(&last_id))
+                .order(ruts::id.asc())
+                .limit(REINDEX_BATCH)
+                .load::<Rut>(conn)?;
+            for r in rows.iter() {
+                if let Some(d) = self.build_doc(conn, &Doc::Rut(r))? {
+                    writer.add_document(d);
+                    doc_count += 1;
+                }
+            }
+            match rows.last() {
+                Some(r) if rows.len() as i64 == REINDEX_BATCH => last_id = r.id.clone(),
+                _ => break,
+            }
+        }
+        let mut last_id = String::new();
+        loop {
+            let rows = items::table
+                .filter(items::id.gt(&last_id))
+                .order(items::id.asc())
+                .limit(REINDEX_BATCH)
+                .load::<Item>(conn)?;
+            for i in rows.iter() {
+                if let Some(d) = self.build_doc(conn, &Doc::Item(i))? {
+                    writer.add_document(d);
+                    doc_count += 1;
+                }
+            }
+            match rows.last() {
+                Some(i) if rows.len() as i64 == REINDEX_BATCH => last_id = i.id.clone(),
+                _ => break,
+            }
+        }
+        let mut last_id = String::new();
+        loop {
+            let rows = etcs::table
+                .filter(etcs::id.gt(&last_id))
+                .order(etcs::id.asc())
+                .limit(REINDEX_BATCH)
+                .load::<Etc>(conn)?;
+            for e in rows.iter() {
+                if let Some(d) = self.build_doc(conn, &Doc::Etc(e))? {
+                    writer.add_document(d);
+                    doc_count += 1;
+                }
+            }
+            match rows.last() {
+                Some(e) if rows.len() as i64 == REINDEX_BATCH => last_id = e.id.clone(),
+                _ => break,
+            }
+        }
+
+        writer.commit().map_err(index_err)?;
+        Ok(doc_count)
+    }
+}
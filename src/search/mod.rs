@@ -0,0 +1,150 @@
+// search mod
+
+// full-text search via a pluggable backend, per env SEARCH_BACKEND=pg|tantivy:
+// pg: generated tsv column, always in sync with the tables, as default
+// tantivy: on-disk index, per feature tantivy-search, synced by hooks in handlers,
+// committed per SEARCH_COMMIT_INTERVAL, rebuild by `rut-server-rust reindex`
+// with the server stopped, as the running server holds the index writer lock
+
+#[cfg(feature = "tantivy-search")]
+pub mod engine;
+pub mod pg;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use std::collections::HashMap;
+
+use crate::errors::ServiceError;
+use crate::model::etc::Etc;
+use crate::model::item::Item;
+use crate::model::rut::Rut;
+use crate::model::search::{HitCount, SearchScope};
+
+// hits in page, headlines per id, total
+pub type Hits<T> = (Vec<T>, HashMap<String, String>, i64);
+
+// the doc to be indexed
+pub enum Doc<'a> {
+    Rut(&'a Rut),
+    Item(&'a Item),
+    Etc(&'a Etc),
+}
+
+// the doc to be indexed again per id, once its relations changed
+pub enum DocID<'a> {
+    Rut(&'a str),
+    Item(&'a str),
+    Etc(&'a str),
+}
+
+pub trait SearchBackend: Send + Sync {
+    fn search_ruts(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Rut>, ServiceError>;
+
+    fn search_items(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Item>, ServiceError>;
+
+    fn search_etcs(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Etc>, ServiceError>;
+
+    // count of hits per type, as facets
+    fn count(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+    ) -> Result<HitCount, ServiceError>;
+
+    // add or replace the doc in index
+    fn index(&self, conn: &PgConnection, doc: &Doc) -> Result<(), ServiceError>;
+
+    fn remove(&self, doc_id: &str) -> Result<(), ServiceError>;
+
+    // commit the pending changes, per task
+    fn commit(&self) -> Result<(), ServiceError>;
+
+    // true if in sync with the tables itself, no hooks needed
+    fn synced(&self) -> bool;
+
+    // rebuild the index from db, return the num of docs
+    fn reindex(&self, conn: &PgConnection) -> Result<usize, ServiceError>;
+}
+
+lazy_static! {
+    static ref BACKEND: Box<dyn SearchBackend> = init_backend();
+}
+
+// get backend per env, pg as default
+fn init_backend() -> Box<dyn SearchBackend> {
+    match dotenv::var("SEARCH_BACKEND").unwrap_or_default().as_str() {
+        #[cfg(feature = "tantivy-search")]
+        "tantivy" => Box::new(
+            engine::TantivyBackend::open(
+                &dotenv::var("SEARCH_INDEX").unwrap_or_else(|_| "search_index".into()),
+            )
+            .expect("Failed to open search index, locked if the server is running"),
+        ),
+        _ => Box::new(pg::PgBackend),
+    }
+}
+
+pub fn backend() -> &'static dyn SearchBackend {
+    BACKEND.as_ref()
+}
+
+// hook in handlers: keep index in sync, not fail the request
+pub fn index_doc(conn: &PgConnection, doc: Doc) {
+    if let Err(e) = backend().index(conn, &doc) {
+        warn!("search index: {}", e);
+    }
+}
+
+// hook in handlers once the relations changed: collected items, tags, done by
+pub fn reindex_doc(conn: &PgConnection, doc_id: DocID) {
+    use crate::schema::{etcs, items, ruts};
+    if backend().synced() {
+        return;
+    }
+    let res = match doc_id {
+        DocID::Rut(i) => ruts::table
+            .filter(ruts::id.eq(i))
+            .get_result::<Rut>(conn)
+            .map(|r| index_doc(conn, Doc::Rut(&r))),
+        DocID::Item(i) => items::table
+            .filter(items::id.eq(i))
+            .get_result::<Item>(conn)
+            .map(|it| index_doc(conn, Doc::Item(&it))),
+        DocID::Etc(i) => etcs::table
+            .filter(etcs::id.eq(i))
+            .get_result::<Etc>(conn)
+            .map(|e| index_doc(conn, Doc::Etc(&e))),
+    };
+    if let Err(e) = res {
+        warn!("search reindex: {}", e);
+    }
+}
+
+pub fn remove_doc(doc_id: &str) {
+    if let Err(e) = backend().remove(doc_id) {
+        warn!("search remove {}: {}", doc_id, e);
+    }
+}
@@ -0,0 +1,205 @@
+// search backend per generated tsv column in postgres, ranked and highlighted
+
+use diesel::deserialize::QueryableByName;
+use diesel::pg::{Pg, PgConnection};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel::{self, RunQueryDsl};
+use std::collections::HashMap;
+
+use crate::errors::ServiceError;
+use crate::model::etc::Etc;
+use crate::model::item::Item;
+use crate::model::rut::Rut;
+use crate::model::search::{
+    clean_keyword, mark_headline, EtcHit, HitCount, ItemHit, RutHit, SearchScope, HL_START,
+    HL_STOP, TS_CONFIG,
+};
+use crate::search::{Doc, Hits, SearchBackend};
+
+pub struct PgBackend;
+
+// params in all search sql:
+// $1: keyword, $2: limit, $3: offset, $4: tag, $5: user, $6: item, $7: category
+// the filter is skipped if param is empty, only public ruts and the comments on them
+const RUT_SCOPE: &str = "AND visibility = 'public' \
+     AND ($4 = '' OR id IN (SELECT rut_id FROM tagruts WHERE tname = $4)) \
+     AND ($5 = '' OR uname = $5) \
+     AND ($6 = '' OR id IN (SELECT rut_id FROM collects WHERE item_id = $6))";
+const ITEM_SCOPE: &str = "AND ($4 = '' OR id IN (SELECT item_id FROM tagitems WHERE tname = $4)) \
+     AND ($5 = '' OR id IN (SELECT item_id FROM staritems WHERE uname = $5 AND flag = 3)) \
+     AND ($7 = '' OR category = $7)";
+const ETC_SCOPE: &str = "AND NOT deleted \
+     AND (rut_id = '' OR rut_id IN (SELECT id FROM ruts WHERE visibility = 'public')) \
+     AND ($4 = '' OR tname = $4 OR id IN (SELECT etc_id FROM tagetcs WHERE tname = $4)) \
+     AND ($5 = '' OR uname = $5) \
+     AND ($6 = '' OR item_id = $6)";
+
+// rank and page in sub-query, then headline just for the rows in page
+fn hit_sql(table: &str, scope: &str, text: &str, order: &str) -> String {
+    format!(
+        "SELECT t.*, h.rank, h.total, ts_headline('{cfg}', {text}, q, '{opt}') AS headline \
+         FROM ( \
+           SELECT id, ts_rank_cd(tsv, q) AS rank, COUNT(*) OVER() AS total \
+           FROM {table}, websearch_to_tsquery('{cfg}', $1) q \
+           WHERE tsv @@ q {scope} \
+           ORDER BY rank DESC, {order} DESC LIMIT $2 OFFSET $3 \
+         ) h JOIN {table} t ON t.id = h.id, websearch_to_tsquery('{cfg}', $1) q \
+         ORDER BY h.rank DESC, t.{order} DESC",
+        cfg = TS_CONFIG,
+        opt = headline_opt(),
+        table = table,
+        scope = scope,
+        text = text,
+        order = order,
+    )
+}
+
+fn count_sql() -> String {
+    format!(
+        "SELECT \
+           (SELECT COUNT(*) FROM ruts WHERE tsv @@ q {rs}) AS rut, \
+           (SELECT COUNT(*) FROM items WHERE tsv @@ q {is}) AS item, \
+           (SELECT COUNT(*) FROM etcs WHERE tsv @@ q {es}) AS etc \
+         FROM websearch_to_tsquery('{cfg}', $1) q",
+        cfg = TS_CONFIG,
+        rs = RUT_SCOPE,
+        is = ITEM_SCOPE,
+        es = ETC_SCOPE,
+    )
+}
+
+fn headline_opt() -> String {
+    format!(
+        "StartSel={}, StopSel={}, MaxWords=30, MinWords=12, MaxFragments=2",
+        HL_START, HL_STOP
+    )
+}
+
+// run the search sql w/ all params bound
+fn load_hits<T: QueryableByName<Pg>>(
+    conn: &PgConnection,
+    sql: String,
+    k: &str,
+    scope: &SearchScope,
+    limit: i64,
+    offset: i64,
+) -> QueryResult<Vec<T>> {
+    diesel::sql_query(sql)
+        .bind::<Text, _>(k)
+        .bind::<BigInt, _>(limit)
+        .bind::<BigInt, _>(offset)
+        .bind::<Text, _>(&scope.tag)
+        .bind::<Text, _>(&scope.user)
+        .bind::<Text, _>(&scope.item)
+        .bind::<Text, _>(&scope.category)
+        .load::<T>(conn)
+}
+
+impl SearchBackend for PgBackend {
+    // search ruts per keyword, in scope
+    // return ruts, headlines per rut id, total
+    fn search_ruts(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Rut>, ServiceError> {
+        let text = "coalesce(nullif(t.content, ''), t.title)";
+        let sql = hit_sql("ruts", RUT_SCOPE, text, "create_at");
+        let hits: Vec<RutHit> = load_hits(conn, sql, &clean_keyword(kw), scope, limit, offset)?;
+
+        let total = hits.first().map(|h| h.total).unwrap_or(0);
+        let mut headlines = HashMap::new();
+        let mut rut_list = Vec::with_capacity(hits.len());
+        for h in hits {
+            headlines.insert(h.rut.id.clone(), mark_headline(&h.headline));
+            rut_list.push(h.rut);
+        }
+
+        Ok((rut_list, headlines, total))
+    }
+
+    // search items per keyword, in scope
+    // return items, headlines per item id, total
+    fn search_items(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Item>, ServiceError> {
+        let text = "coalesce(nullif(t.detail, ''), t.title)";
+        let sql = hit_sql("items", ITEM_SCOPE, text, "rut_count");
+        let hits: Vec<ItemHit> = load_hits(conn, sql, &clean_keyword(kw), scope, limit, offset)?;
+
+        let total = hits.first().map(|h| h.total).unwrap_or(0);
+        let mut headlines = HashMap::new();
+        let mut item_list = Vec::with_capacity(hits.len());
+        for h in hits {
+            headlines.insert(h.item.id.clone(), mark_headline(&h.headline));
+            item_list.push(h.item);
+        }
+
+        Ok((item_list, headlines, total))
+    }
+
+    // search etcs per keyword, in scope
+    // return etcs, headlines per etc id, total
+    fn search_etcs(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Hits<Etc>, ServiceError> {
+        let sql = hit_sql("etcs", ETC_SCOPE, "t.content", "post_at");
+        let hits: Vec<EtcHit> = load_hits(conn, sql, &clean_keyword(kw), scope, limit, offset)?;
+
+        let total = hits.first().map(|h| h.total).unwrap_or(0);
+        let mut headlines = HashMap::new();
+        let mut etc_list = Vec::with_capacity(hits.len());
+        for h in hits {
+            headlines.insert(h.etc.id.clone(), mark_headline(&h.headline));
+            etc_list.push(h.etc);
+        }
+
+        Ok((etc_list, headlines, total))
+    }
+
+    // count of hits per type
+    fn count(
+        &self,
+        conn: &PgConnection,
+        kw: &str,
+        scope: &SearchScope,
+    ) -> Result<HitCount, ServiceError> {
+        let counts: Vec<HitCount> = load_hits(conn, count_sql(), &clean_keyword(kw), scope, 0, 0)?;
+        Ok(counts.into_iter().next().unwrap_or(HitCount { rut: 0, item: 0, etc: 0 }))
+    }
+
+    // tsv is generated column, nothing to sync
+    fn index(&self, _conn: &PgConnection, _doc: &Doc) -> Result<(), ServiceError> {
+        Ok(())
+    }
+
+    fn remove(&self, _doc_id: &str) -> Result<(), ServiceError> {
+        Ok(())
+    }
+
+    fn commit(&self) -> Result<(), ServiceError> {
+        Ok(())
+    }
+
+    fn synced(&self) -> bool {
+        true
+    }
+
+    fn reindex(&self, _conn: &PgConnection) -> Result<usize, ServiceError> {
+        Ok(0)
+    }
+}
@@ -0,0 +1,224 @@
+// deliver webhook payload, in sync actor as blocking http
+
+use actix::prelude::*;
+use actix::{Actor, SyncContext};
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::db::webhook::{get_webhook, new_delivery};
+use crate::errors::ServiceError;
+use crate::model::msg::{DeliveryMsg, Msg};
+use crate::model::webhook::{Delivery, FlushDeliveries, TestWebhook, Webhook};
+use crate::DbPool;
+
+// max attempts before mark as failed
+const MAX_ATTEMPTS: i32 = 6;
+// backoff base(secs), double per attempt
+const BACKOFF_BASE: i64 = 30;
+// deliveries per flush
+const FLUSH_BATCH: i64 = 50;
+// secs to hold the claimed as sending, longer than a batch with timeout,
+// then claim again in case the worker crashed in sending
+const CLAIM_LEASE: i64 = 900;
+// max chars of response body to log
+const BODY_LOG_LEN: usize = 1024;
+
+pub struct Deliverer {
+    pub pool: DbPool,
+    pub client: reqwest::Client,
+}
+
+impl Actor for Deliverer {
+    type Context = SyncContext<Self>;
+}
+
+pub type DeliverAddr = Addr<Deliverer>;
+
+pub fn init_deliverer(pool: DbPool) -> DeliverAddr {
+    let timeout = dotenv::var("WEBHOOK_TIMEOUT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(10);
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout))
+        .build()
+        .expect("Failed to create http client.");
+
+    SyncArbiter::start(2, move || Deliverer {
+        pool: pool.clone(),
+        client: client.clone(),
+    })
+}
+
+// handle msg from task::Scheduler, deliver the pending in due
+impl Handler<FlushDeliveries> for Deliverer {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, _: FlushDeliveries, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::deliveries::dsl::*;
+        let conn = &self.pool.get()?;
+
+        // claim at once, not to be sent twice by the other worker
+        let due_list = diesel::sql_query(claim_sql()).load::<Delivery>(conn)?;
+
+        let mut ok_count = 0;
+        for d in due_list.iter() {
+            use crate::schema::webhooks::dsl::{id as wid, webhooks};
+            let webhook_q = webhooks
+                .filter(&wid.eq(&d.webhook_id))
+                .load::<Webhook>(conn)
+                .map(|mut w| w.pop());
+            let res = match webhook_q {
+                Ok(Some(ref w)) if w.active => self.deliver(conn, w, d),
+                Ok(_) => diesel::update(d)
+                    .set(status.eq("failed"))
+                    .get_result::<Delivery>(conn)
+                    .map_err(|e| e.into()),
+                Err(e) => Err(e.into()),
+            };
+            // log the error on the row and go on, claim again after the lease
+            match res {
+                Ok(ref dd) if dd.status == "success" => ok_count += 1,
+                Ok(_) => (),
+                Err(e) => {
+                    let err_body: String = e.to_string().chars().take(BODY_LOG_LEN).collect();
+                    diesel::update(d)
+                        .set(response_body.eq(err_body))
+                        .execute(conn)
+                        .ok();
+                }
+            }
+        }
+
+        Ok(Msg {
+            status: 200,
+            message: format!("Delivered {}/{}", ok_count, due_list.len()),
+        })
+    }
+}
+
+// handle msg from api::webhook.test, deliver a ping at once
+impl Handler<TestWebhook> for Deliverer {
+    type Result = Result<DeliveryMsg, ServiceError>;
+
+    fn handle(&mut self, tw: TestWebhook, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::deliveries::dsl::*;
+        let conn = &self.pool.get()?;
+
+        let webhook_q = get_webhook(conn, &tw.webhook_id, &tw.uname)?;
+        let mut ping = new_delivery(
+            &webhook_q.id,
+            "ping",
+            &serde_json::json!({ "webhook_id": &webhook_q.id }),
+        )?;
+        // hold from flush, as delivered here
+        ping.status = "sending".to_owned();
+        ping.next_at = ping.next_at + Duration::seconds(CLAIM_LEASE);
+        let ping = diesel::insert_into(deliveries)
+            .values(&ping)
+            .get_result::<Delivery>(conn)?;
+        let delivered = self.deliver(conn, &webhook_q, &ping)?;
+
+        Ok(DeliveryMsg {
+            status: 200,
+            message: delivered.status.clone(),
+            delivery: delivered,
+        })
+    }
+}
+
+impl Deliverer {
+    // post signed payload, log the result, retry with backoff if failed
+    fn deliver(
+        &self,
+        conn: &PgConnection,
+        webhook: &Webhook,
+        delivery: &Delivery,
+    ) -> Result<Delivery, ServiceError> {
+        use crate::schema::deliveries::dsl::*;
+
+        // unix seconds per attempt, signed w/ payload so a replay can be told
+        let timestamp = Utc::now().timestamp().to_string();
+        let signed = sign(&webhook.secret, &timestamp, &delivery.payload);
+        let res = self
+            .client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "ruthub-webhook")
+            .header("X-Rut-Event", delivery.event.as_str())
+            .header("X-Rut-Delivery", delivery.id.as_str())
+            .header("X-Rut-Timestamp", timestamp.as_str())
+            .header("X-Rut-Signature", format!("sha256={}", signed))
+            .body(delivery.payload.clone())
+            .send();
+
+        let (code, body, ok) = match res {
+            Ok(mut r) => {
+                let body = r.text().unwrap_or_default();
+                (Some(r.status().as_u16() as i32), body, r.status().is_success())
+            }
+            Err(e) => (None, e.to_string(), false),
+        };
+
+        let now = Utc::now().naive_utc();
+        let tried = delivery.attempts + 1;
+        let (new_status, next) = if ok {
+            ("success", now)
+        } else if tried >= MAX_ATTEMPTS {
+            ("failed", now)
+        } else {
+            ("pending", now + Duration::seconds(BACKOFF_BASE << (tried - 1)))
+        };
+
+        let delivered = diesel::update(delivery)
+            .set((
+                status.eq(new_status),
+                attempts.eq(tried),
+                response_code.eq(code),
+                response_body.eq(body.chars().take(BODY_LOG_LEN).collect::<String>()),
+                next_at.eq(next),
+                deliver_at.eq(Some(now)),
+            ))
+            .get_result::<Delivery>(conn)?;
+
+        Ok(delivered)
+    }
+}
+
+// claim the due, pending or the sending but lease expired, as sending;
+// rows locked by the other worker skipped
+fn claim_sql() -> String {
+    format!(
+        "
+    UPDATE deliveries SET status = 'sending',
+        next_at = (NOW() AT TIME ZONE 'UTC') + INTERVAL '{lease} seconds'
+    WHERE id IN (
+        SELECT id FROM deliveries
+        WHERE status IN ('pending', 'sending') AND next_at <= (NOW() AT TIME ZONE 'UTC')
+        ORDER BY next_at
+        LIMIT {batch}
+        FOR UPDATE SKIP LOCKED
+    )
+    RETURNING *
+",
+        lease = CLAIM_LEASE,
+        batch = FLUSH_BATCH,
+    )
+}
+
+// hmac-sha256 of "timestamp.payload", in hex
+pub fn sign(secret: &str, timestamp: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.input(timestamp.as_bytes());
+    mac.input(b".");
+    mac.input(payload.as_bytes());
+    mac.result()
+        .code()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
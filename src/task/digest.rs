@@ -0,0 +1,103 @@
+// send email digest, in sync actor as blocking io
+
+use actix::prelude::*;
+use actix::{Actor, SyncContext};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::db::digest::{collect_digest, get_setting};
+use crate::errors::ServiceError;
+use crate::mail::digest::render_digest;
+use crate::mail::{init_mailer, Mailer};
+use crate::model::digest::{DigestSetting, SendDigests};
+use crate::model::msg::Msg;
+use crate::DbPool;
+
+pub struct Digester {
+    pub pool: DbPool,
+    pub mailer: Box<dyn Mailer>,
+}
+
+impl Actor for Digester {
+    type Context = SyncContext<Self>;
+}
+
+pub type DigestAddr = Addr<Digester>;
+
+pub fn init_digester(pool: DbPool) -> DigestAddr {
+    SyncArbiter::start(1, move || Digester {
+        pool: pool.clone(),
+        mailer: init_mailer(),
+    })
+}
+
+// handle msg from task::Scheduler, send digest to the user in due
+impl Handler<SendDigests> for Digester {
+    type Result = Result<Msg, ServiceError>;
+
+    fn handle(&mut self, _: SendDigests, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::users::dsl::{email, uname, users};
+        let conn = &self.pool.get()?;
+
+        let now = Utc::now().naive_utc();
+        let user_list = users
+            .filter(&email.ne(""))
+            .select((uname, email))
+            .load::<(String, String)>(conn)?;
+
+        let mut sent_count = 0;
+        for (u_name, u_email) in user_list.iter() {
+            let setting = get_setting(conn, u_name)?;
+            let period = match setting.frequency.as_str() {
+                "daily" => Duration::days(1),
+                "weekly" => Duration::days(7),
+                _ => continue,
+            };
+            // check if due
+            let since = match setting.last_sent {
+                Some(t) if t + period > now => continue,
+                Some(t) => t,
+                None => now - period,
+            };
+
+            let digest = collect_digest(conn, u_name, since)?;
+            if !digest.is_empty() {
+                let mail = render_digest(u_email, u_name, &digest);
+                if let Err(e) = self.mailer.send(&mail) {
+                    warn!("digest to {}: {}", u_name, e);
+                    continue;
+                }
+                sent_count += 1;
+            }
+            save_last_sent(conn, setting)?;
+        }
+
+        Ok(Msg {
+            status: 200,
+            message: format!("Sent {}", sent_count),
+        })
+    }
+}
+
+fn save_last_sent(conn: &PgConnection, setting: DigestSetting) -> Result<(), ServiceError> {
+    use crate::schema::digestsettings::dsl::*;
+
+    let now = Some(Utc::now().naive_utc());
+    if setting.id == "" {
+        let new_setting = DigestSetting {
+            id: format!("{}", uuid::Uuid::new_v4()),
+            last_sent: now,
+            ..setting
+        };
+        diesel::insert_into(digestsettings)
+            .values(&new_setting)
+            .execute(conn)?;
+    } else {
+        diesel::update(&setting)
+            .set(last_sent.eq(now))
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
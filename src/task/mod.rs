@@ -0,0 +1,71 @@
+// task mod
+
+// some periodic task,
+// just send msg to Db Actor per interval, handled by msg-handler of Actor
+
+pub mod deliver;
+pub mod digest;
+
+use actix::prelude::*;
+use std::time::Duration;
+
+use crate::model::digest::SendDigests;
+use crate::model::recommend::RefreshItemSims;
+use crate::model::search::CommitIndex;
+use crate::model::tag::RefreshRelatedTags;
+use crate::model::webhook::FlushDeliveries;
+use crate::model::{RecomputeVotes, RenderHtml};
+use crate::task::deliver::DeliverAddr;
+use crate::task::digest::DigestAddr;
+use crate::DbAddr;
+
+pub struct Scheduler {
+    pub db: DbAddr,
+    pub deliver: DeliverAddr,
+    pub digest: DigestAddr,
+}
+
+impl Actor for Scheduler {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        // html of content saved before markdown rendering
+        self.db.do_send(RenderHtml);
+        // related tags: once on start, then per interval
+        self.db.do_send(RefreshRelatedTags);
+        ctx.run_interval(get_interval("RELATED_TAGS_INTERVAL", 3600), |act, _| {
+            act.db.do_send(RefreshRelatedTags);
+        });
+        // item similarity for recommendations: once on start, then per interval
+        self.db.do_send(RefreshItemSims);
+        ctx.run_interval(get_interval("ITEM_SIMS_INTERVAL", 3600), |act, _| {
+            act.db.do_send(RefreshItemSims);
+        });
+        // vote per the weights of score, may changed since last run
+        self.db.do_send(RecomputeVotes);
+        ctx.run_interval(get_interval("VOTES_INTERVAL", 3600), |act, _| {
+            act.db.do_send(RecomputeVotes);
+        });
+        // search index changed by hooks, commit in batch
+        ctx.run_interval(get_interval("SEARCH_COMMIT_INTERVAL", 5), |act, _| {
+            act.db.do_send(CommitIndex);
+        });
+        // webhook deliveries in due, incl. retry
+        ctx.run_interval(get_interval("WEBHOOK_INTERVAL", 10), |act, _| {
+            act.deliver.do_send(FlushDeliveries);
+        });
+        // email digest, check who is in due
+        ctx.run_interval(get_interval("DIGEST_INTERVAL", 3600), |act, _| {
+            act.digest.do_send(SendDigests);
+        });
+    }
+}
+
+// get interval(secs) from env or use default
+fn get_interval(key: &str, default: u64) -> Duration {
+    let secs = dotenv::var(key)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default);
+    Duration::from_secs(secs)
+}
@@ -0,0 +1,122 @@
+// feed helpers: render atom and rss by hand
+
+use chrono::NaiveDateTime;
+
+use crate::model::feed::Feed;
+
+pub fn to_atom(feed: &Feed, self_link: &str) -> String {
+    let mut xml = String::with_capacity(1024 + feed.entries.len() * 512);
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str("\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{}</id>\n", escape_xml(&feed.id)));
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", rfc3339(&feed.updated)));
+    xml.push_str(&format!(
+        "  <link rel=\"alternate\" href=\"{}\"/>\n",
+        escape_xml(&feed.link)
+    ));
+    xml.push_str(&format!(
+        "  <link rel=\"self\" href=\"{}\"/>\n",
+        escape_xml(self_link)
+    ));
+    for e in feed.entries.iter() {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&e.id)));
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&e.title)));
+        xml.push_str(&format!("    <updated>{}</updated>\n", rfc3339(&e.updated)));
+        xml.push_str(&format!(
+            "    <link rel=\"alternate\" href=\"{}\"/>\n",
+            escape_xml(&e.link)
+        ));
+        xml.push_str(&format!(
+            "    <author><name>{}</name></author>\n",
+            escape_xml(&e.author)
+        ));
+        if e.summary != "" {
+            xml.push_str(&format!(
+                "    <summary>{}</summary>\n",
+                escape_xml(&e.summary)
+            ));
+        }
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+pub fn to_rss(feed: &Feed, self_link: &str) -> String {
+    let mut xml = String::with_capacity(1024 + feed.entries.len() * 512);
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(
+        "\n<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<channel>\n",
+    );
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!("  <link>{}</link>\n", escape_xml(&feed.link)));
+    xml.push_str(&format!(
+        "  <description>{}</description>\n",
+        escape_xml(&feed.title)
+    ));
+    xml.push_str(&format!(
+        "  <lastBuildDate>{}</lastBuildDate>\n",
+        http_date(&feed.updated)
+    ));
+    xml.push_str(&format!(
+        "  <atom:link rel=\"self\" type=\"application/rss+xml\" href=\"{}\"/>\n",
+        escape_xml(self_link)
+    ));
+    for e in feed.entries.iter() {
+        xml.push_str("  <item>\n");
+        xml.push_str(&format!(
+            "    <guid isPermaLink=\"false\">{}</guid>\n",
+            escape_xml(&e.id)
+        ));
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&e.title)));
+        xml.push_str(&format!("    <link>{}</link>\n", escape_xml(&e.link)));
+        xml.push_str(&format!("    <pubDate>{}</pubDate>\n", http_date(&e.updated)));
+        xml.push_str(&format!(
+            "    <dc:creator>{}</dc:creator>\n",
+            escape_xml(&e.author)
+        ));
+        if e.summary != "" {
+            xml.push_str(&format!(
+                "    <description>{}</description>\n",
+                escape_xml(&e.summary)
+            ));
+        }
+        xml.push_str("  </item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+// as utc in rfc3339, for atom
+pub fn rfc3339(t: &NaiveDateTime) -> String {
+    t.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+// as utc in rfc2822 like, for rss and http header
+pub fn http_date(t: &NaiveDateTime) -> String {
+    t.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+pub fn parse_http_date(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s.trim(), "%a, %d %b %Y %H:%M:%S GMT").ok()
+}
+
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // invalid in xml 1.0
+            c if (c as u32) < 0x20 && c != '\t' && c != '\n' && c != '\r' => (),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,95 @@
+// markdown helpers: render user content, then sanitize
+
+use ammonia::{Builder, UrlRelative};
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+use std::collections::HashSet;
+
+// render markdown to safe html
+pub fn md_to_html(text: &str) -> String {
+    if text.trim().is_empty() {
+        return String::new();
+    }
+
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_STRIKETHROUGH);
+    opts.insert(Options::ENABLE_TABLES);
+    let parser = Parser::new_ext(text, opts);
+
+    // images only from http(s), else keep the alt text only
+    let mut drop_img = false;
+    let events = parser.filter(|ev| match ev {
+        Event::Start(Tag::Image(_, src, _)) => {
+            drop_img = !is_http(src);
+            !drop_img
+        }
+        Event::End(Tag::Image(_, _, _)) => {
+            let keep = !drop_img;
+            drop_img = false;
+            keep
+        }
+        _ => true,
+    });
+
+    let mut raw_html = String::with_capacity(text.len() * 3 / 2);
+    html::push_html(&mut raw_html, events);
+
+    clean_html(&raw_html)
+}
+
+// strict sanitizer, links as nofollow,
+// relative and protocol-relative urls stripped, e.g. src of raw <img>
+fn clean_html(raw: &str) -> String {
+    let mut schemes = HashSet::new();
+    schemes.insert("http");
+    schemes.insert("https");
+    schemes.insert("mailto");
+
+    Builder::default()
+        .url_schemes(schemes)
+        .url_relative(UrlRelative::Deny)
+        .link_rel(Some("nofollow noopener noreferrer"))
+        .clean(raw)
+        .to_string()
+}
+
+fn is_http(url: &str) -> bool {
+    let u = url.trim().to_lowercase();
+    u.starts_with("http://") || u.starts_with("https://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::md_to_html;
+
+    #[test]
+    fn keep_http_image() {
+        let h = md_to_html("![cover](https://example.com/a.png)");
+        assert!(h.contains(r#"src="https://example.com/a.png""#));
+    }
+
+    #[test]
+    fn strip_raw_image_src() {
+        let h = md_to_html(r#"<img src="//tracker.example/p.gif">"#);
+        assert!(!h.contains("tracker.example"));
+        let h = md_to_html(r#"<img src="p.gif">"#);
+        assert!(!h.contains("p.gif"));
+        let h = md_to_html(r#"<img src="data:image/gif;base64,R0lGOD">"#);
+        assert!(!h.contains("data:"));
+    }
+
+    #[test]
+    fn strip_non_http_image() {
+        let h = md_to_html("![x](//tracker.example/p.gif)");
+        assert!(!h.contains("tracker.example"));
+        assert!(!h.contains("<img"));
+    }
+
+    #[test]
+    fn strip_js_link() {
+        let h = md_to_html("[click](javascript:alert(1))");
+        assert!(!h.contains("javascript"));
+        let h = md_to_html(r#"<a href="javascript:alert(1)">click</a>"#);
+        assert!(!h.contains("javascript"));
+        assert!(h.contains("click"));
+    }
+}
@@ -2,4 +2,6 @@
 
 // some helper
 
+pub mod feed;
+pub mod md;
 pub mod share;
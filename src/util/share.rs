@@ -73,3 +73,11 @@ pub fn get_v(map_ref: &HashMap<String, String>, k: &str) -> String {
     }
     return "".to_string();
 }
+
+// base url of site, to build link in mail, feed
+pub fn site_url() -> String {
+    dotenv::var("SITE_URL")
+        .unwrap_or_else(|_| "https://ruthub.com".into())
+        .trim_end_matches('/')
+        .to_owned()
+}